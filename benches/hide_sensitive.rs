@@ -0,0 +1,73 @@
+//! Throughput of `hide_sensitive` on multi-megabyte logs, run with `cargo bench`.
+
+use std::{hint::black_box, time::Instant};
+
+use semantic_release::hide_sensitive::{hide_sensitive_with, SecretsConfig};
+
+const LOG_SIZE: usize = 8 * 1024 * 1024;
+const ITERATIONS: u32 = 5;
+
+fn main() {
+    for secrets in [0, 10, 1000] {
+        let env: Vec<(String, String)> = (0..secrets)
+            .map(|i| {
+                (
+                    format!("BENCH_TOKEN_{}", i),
+                    format!("s3cr3t-value-{:06}", i),
+                )
+            })
+            .collect();
+
+        let hide_sensitive_fn = hide_sensitive_with(env.clone(), &SecretsConfig::default());
+
+        let log = generate_log(&env);
+
+        let start = Instant::now();
+
+        for _ in 0..ITERATIONS {
+            for line in log.lines() {
+                black_box(hide_sensitive_fn(line));
+            }
+        }
+
+        let elapsed = start.elapsed() / ITERATIONS;
+        let throughput = log.len() as f64 / 1024.0 / 1024.0 / elapsed.as_secs_f64();
+
+        println!(
+            "{:>5} secrets, {} MiB log: {:>8.2?} per run, {:.1} MiB/s",
+            secrets,
+            log.len() / 1024 / 1024,
+            elapsed,
+            throughput
+        );
+    }
+}
+
+/// A log of plausible lines, some of them leaking one of the secrets.
+fn generate_log(env: &[(String, String)]) -> String {
+    let mut log = String::with_capacity(LOG_SIZE);
+    let mut i = 0;
+
+    while log.len() < LOG_SIZE {
+        match (i % 50, env.get(i % env.len().max(1))) {
+            (0, Some((_, secret))) => {
+                log.push_str(&format!("npm notice Publishing with token {}\n", secret))
+            }
+            (1, _) => log.push_str(&format!(
+                "remote: https://github.com/owner/repo/pull/{} Resolving deltas: 100% ({}/{})\n",
+                i,
+                i,
+                i * 2
+            )),
+            _ => log.push_str(&format!(
+                "[{:06}] Compiling semantic-release v0.1.0 (/home/runner/work/repo) in {}ms\n",
+                i,
+                i % 997
+            )),
+        }
+
+        i += 1;
+    }
+
+    log
+}
@@ -0,0 +1,180 @@
+use regex::Regex;
+use serde::Serialize;
+
+use crate::get_config::{ReleaseRule, ReleaseType};
+
+/// A commit message parsed following the [Conventional Commits](https://www.conventionalcommits.org) specification.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ConventionalCommit {
+    /// The type of the commit, e.g. `feat`. `None` when the message isn't conventional.
+    pub commit_type: Option<String>,
+    pub scope: Option<String>,
+    /// The first line of the message, without the type and scope.
+    pub subject: String,
+    /// The descriptions of the breaking changes, from the `BREAKING CHANGE:` footers or
+    /// the subject when the type is followed by `!`.
+    pub breaking_changes: Vec<String>,
+}
+
+impl ConventionalCommit {
+    /// Parse a commit message.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use semantic_release::analyze_commits::ConventionalCommit;
+    /// let commit = ConventionalCommit::parse("feat(cli)!: drop the --verbose option");
+    ///
+    /// assert_eq!(commit.commit_type.as_deref(), Some("feat"));
+    /// assert_eq!(commit.scope.as_deref(), Some("cli"));
+    /// assert_eq!(commit.breaking_changes, vec!["drop the --verbose option"]);
+    /// ```
+    pub fn parse(message: &str) -> ConventionalCommit {
+        let header =
+            Regex::new(r"^(\w+)(?:\(([^)]*)\))?(!)?: (.+)$").expect("should be a valid regex");
+        let footer = Regex::new(r"(?ms)^BREAKING[ -]CHANGES?:\s*(.+?)\s*(?:\n\n|\z)")
+            .expect("should be a valid regex");
+
+        let mut lines = message.trim().splitn(2, '\n');
+        let first_line = lines.next().unwrap_or_default().trim();
+        let body = lines.next().unwrap_or_default();
+
+        let mut breaking_changes: Vec<String> = footer
+            .captures_iter(body)
+            .map(|caps| caps[1].to_string())
+            .collect();
+
+        match header.captures(first_line) {
+            Some(caps) => {
+                let subject = caps[4].to_string();
+
+                if caps.get(3).is_some() && breaking_changes.is_empty() {
+                    breaking_changes.push(subject.clone());
+                }
+
+                ConventionalCommit {
+                    commit_type: Some(caps[1].to_lowercase()),
+                    scope: caps.get(2).map(|scope| scope.as_str().to_string()),
+                    subject,
+                    breaking_changes,
+                }
+            }
+            None => ConventionalCommit {
+                commit_type: None,
+                scope: None,
+                subject: first_line.to_string(),
+                breaking_changes,
+            },
+        }
+    }
+
+    pub fn is_breaking(&self) -> bool {
+        !self.breaking_changes.is_empty()
+    }
+}
+
+/// Test if `rule` applies to `commit`.
+fn matches_rule(rule: &ReleaseRule, commit: &ConventionalCommit) -> bool {
+    rule.commit_type
+        .as_ref()
+        .is_none_or(|commit_type| commit.commit_type.as_ref() == Some(commit_type))
+        && rule
+            .scope
+            .as_ref()
+            .is_none_or(|scope| commit.scope.as_ref() == Some(scope))
+        && rule
+            .breaking
+            .is_none_or(|breaking| commit.is_breaking() == breaking)
+}
+
+/// Get the type of release a commit triggers.
+///
+/// The first of the `release_rules` matching the commit decides, falling back to the
+/// default rules: breaking changes trigger a major release, `feat` a minor one, and
+/// `fix` and `perf` a patch one.
+pub fn analyze_commit(commit: &ConventionalCommit, release_rules: &[ReleaseRule]) -> ReleaseType {
+    if let Some(rule) = release_rules.iter().find(|rule| matches_rule(rule, commit)) {
+        return rule.release;
+    }
+
+    if commit.is_breaking() {
+        return ReleaseType::Major;
+    }
+
+    match commit.commit_type.as_deref() {
+        Some("feat") => ReleaseType::Minor,
+        Some("fix") | Some("perf") => ReleaseType::Patch,
+        _ => ReleaseType::None,
+    }
+}
+
+/// Get the type of release triggered by a set of commit messages, the highest of any commit.
+///
+/// # Example
+///
+/// ```
+/// # use semantic_release::{analyze_commits::analyze_commits, get_config::ReleaseType};
+/// let messages = ["docs: fix typo", "fix: handle empty input", "feat: add --output"];
+///
+/// assert_eq!(analyze_commits(messages, &[]), ReleaseType::Minor);
+/// ```
+pub fn analyze_commits<'a>(
+    messages: impl IntoIterator<Item = &'a str>,
+    release_rules: &[ReleaseRule],
+) -> ReleaseType {
+    messages
+        .into_iter()
+        .map(|message| analyze_commit(&ConventionalCommit::parse(message), release_rules))
+        .max()
+        .unwrap_or(ReleaseType::None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_breaking_change_footer() {
+        let commit = ConventionalCommit::parse(
+            "refactor: rename the config file\n\nBREAKING CHANGE: `.semrelrc` is now `release.toml`\n\nCloses #12",
+        );
+
+        assert_eq!(commit.commit_type.as_deref(), Some("refactor"));
+        assert_eq!(
+            commit.breaking_changes,
+            vec!["`.semrelrc` is now `release.toml`"]
+        );
+        assert_eq!(analyze_commit(&commit, &[]), ReleaseType::Major);
+    }
+
+    #[test]
+    fn test_release_rules() {
+        let release_rules = vec![
+            ReleaseRule {
+                commit_type: Some("docs".to_string()),
+                scope: Some("readme".to_string()),
+                breaking: None,
+                release: ReleaseType::Patch,
+            },
+            ReleaseRule {
+                commit_type: Some("feat".to_string()),
+                scope: Some("internal".to_string()),
+                breaking: None,
+                release: ReleaseType::None,
+            },
+        ];
+
+        assert_eq!(
+            analyze_commits(["docs(readme): add usage"], &release_rules),
+            ReleaseType::Patch
+        );
+        assert_eq!(
+            analyze_commits(["feat(internal): add helper"], &release_rules),
+            ReleaseType::None
+        );
+        assert_eq!(
+            analyze_commits(["Merge branch 'next'", "chore: bump"], &release_rules),
+            ReleaseType::None
+        );
+    }
+}
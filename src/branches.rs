@@ -0,0 +1,136 @@
+use regex::Regex;
+
+/// Characters that make a `branches` entry a glob rather than a plain branch name.
+const GLOB_CHARS: [char; 10] = ['*', '?', '[', ']', '{', '}', '(', ')', '+', '!'];
+
+/// Test if a `branches` entry is a glob rather than a plain branch name.
+pub fn is_glob(pattern: &str) -> bool {
+    pattern.contains(GLOB_CHARS)
+}
+
+/// Translate a branch glob to an anchored regex pattern.
+///
+/// Supports the micromatch syntax used in semantic-release `branches` configurations:
+/// `*`, `?`, `[...]` classes, `{a,b}` braces and the `+(...)`, `*(...)`, `?(...)` and
+/// `@(...)` extglobs.
+///
+/// # Example
+///
+/// ```
+/// # use semantic_release::branches::glob_to_regex;
+/// assert_eq!(glob_to_regex("+([0-9]).x"), r"^(?:[0-9])+\.x$");
+/// ```
+pub fn glob_to_regex(pattern: &str) -> String {
+    let chars: Vec<char> = pattern.chars().collect();
+
+    let mut regex = String::from("^");
+    // Closing character and regex quantifier of the groups currently open.
+    let mut groups: Vec<(char, &str)> = vec![];
+
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        let next = chars.get(i + 1).copied();
+
+        match c {
+            '+' | '*' | '?' | '@' if next == Some('(') => {
+                let quantifier = match c {
+                    '+' => "+",
+                    '*' => "*",
+                    '?' => "?",
+                    _ => "",
+                };
+
+                groups.push((')', quantifier));
+                regex.push_str("(?:");
+                i += 1;
+            }
+            '*' => regex.push_str("[^/]*"),
+            '?' => regex.push_str("[^/]"),
+            '{' => {
+                groups.push(('}', ""));
+                regex.push_str("(?:");
+            }
+            ',' if groups.last().map(|(close, _)| *close) == Some('}') => regex.push('|'),
+            '|' if groups.last().map(|(close, _)| *close) == Some(')') => regex.push('|'),
+            ')' | '}' if groups.last().map(|(close, _)| *close) == Some(c) => {
+                let (_, quantifier) = groups.pop().expect("group should be open");
+
+                regex.push(')');
+                regex.push_str(quantifier);
+            }
+            '[' => match chars[i + 1..].iter().position(|c| *c == ']') {
+                Some(end) => {
+                    let class: String = chars[i + 1..i + 1 + end].iter().collect();
+                    let class = class
+                        .strip_prefix('!')
+                        .map_or(class.clone(), |negated| format!("^{}", negated));
+
+                    regex.push('[');
+                    regex.push_str(&class.replace('\\', "\\\\"));
+                    regex.push(']');
+                    i += end + 1;
+                }
+                None => regex.push_str(r"\["),
+            },
+            '\\' if next.is_some() => {
+                regex.push_str(&regex::escape(&next.unwrap_or_default().to_string()));
+                i += 1;
+            }
+            _ => regex.push_str(&regex::escape(&c.to_string())),
+        }
+
+        i += 1;
+    }
+
+    regex.push('$');
+
+    regex
+}
+
+/// Test if `branch` matches a `branches` entry, either by name or as a glob.
+///
+/// # Example
+///
+/// ```
+/// # use semantic_release::branches::matches;
+/// assert!(matches("+([0-9])?(.{+([0-9]),x}).x", "1.2.x"));
+/// assert!(matches("main", "main"));
+/// assert!(!matches("main", "next"));
+/// ```
+pub fn matches(pattern: &str, branch: &str) -> bool {
+    if !is_glob(pattern) {
+        return pattern == branch;
+    }
+
+    Regex::new(&glob_to_regex(pattern))
+        .map(|regex| regex.is_match(branch))
+        .unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_maintenance_branches() {
+        let pattern = "+([0-9])?(.{+([0-9]),x}).x";
+
+        assert!(matches(pattern, "1.x"));
+        assert!(matches(pattern, "1.x.x"));
+        assert!(matches(pattern, "12.3.x"));
+        assert!(!matches(pattern, "main"));
+        assert!(!matches(pattern, "1.2"));
+        assert!(!matches(pattern, "x.x"));
+    }
+
+    #[test]
+    fn test_simple_globs() {
+        assert!(matches("release/*", "release/1.0"));
+        assert!(!matches("release/*", "release/1.0/hotfix"));
+        assert!(matches("{beta,alpha}", "beta"));
+        assert!(matches("v[0-9]", "v1"));
+        assert!(!matches("v[!0-9]", "v1"));
+    }
+}
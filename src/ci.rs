@@ -0,0 +1,551 @@
+use std::{collections::HashMap, fs};
+
+/// The CI services that can be detected from the environment.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CiService {
+    GitHubActions,
+    GitLab,
+    CircleCi,
+    Travis,
+    Jenkins,
+    AzurePipelines,
+    Buildkite,
+    Drone,
+    Woodpecker,
+    BitbucketPipelines,
+    TeamCity,
+    /// An unknown service that sets `CI=true`.
+    Generic,
+}
+
+/// What a git reference reported by a CI service points to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GitRef {
+    Branch(String),
+    Tag(String),
+    PullRequest(u64),
+}
+
+/// Normalize a reference reported by a CI service.
+///
+/// Full references such as `refs/heads/main`, `refs/tags/v1.0.0`, `refs/pull/7/merge`
+/// or GitLab's `refs/merge-requests/7/head` are resolved, anything else is
+/// considered a plain branch name.
+///
+/// # Example
+///
+/// ```
+/// # use semantic_release::ci::{parse_ref, GitRef};
+/// assert_eq!(parse_ref("refs/heads/main"), GitRef::Branch("main".to_string()));
+/// assert_eq!(parse_ref("refs/tags/v1.0.0"), GitRef::Tag("v1.0.0".to_string()));
+/// assert_eq!(parse_ref("refs/pull/7/merge"), GitRef::PullRequest(7));
+/// ```
+pub fn parse_ref(reference: &str) -> GitRef {
+    if let Some(branch) = reference.strip_prefix("refs/heads/") {
+        return GitRef::Branch(branch.to_string());
+    }
+
+    if let Some(tag) = reference.strip_prefix("refs/tags/") {
+        return GitRef::Tag(tag.to_string());
+    }
+
+    let pull_request = reference
+        .strip_prefix("refs/pull/")
+        .or_else(|| reference.strip_prefix("refs/merge-requests/"))
+        .and_then(|rest| rest.split('/').next())
+        .and_then(|number| number.parse().ok());
+
+    if let Some(number) = pull_request {
+        return GitRef::PullRequest(number);
+    }
+
+    if let Some(branch) = reference
+        .strip_prefix("refs/remotes/")
+        .and_then(|rest| rest.split_once('/'))
+        .map(|(_, branch)| branch)
+    {
+        return GitRef::Branch(branch.to_string());
+    }
+
+    GitRef::Branch(reference.to_string())
+}
+
+/// Information about the CI environment the command runs in, in the spirit of `env-ci`.
+///
+/// `branch` is the branch the build runs for, which for pull requests is the
+/// target branch while `pr_branch` is the source branch. Both are normalized
+/// branch names; builds triggered by a tag have `tag` set and no `branch`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CiEnv {
+    pub is_ci: bool,
+    pub service: Option<CiService>,
+    pub branch: Option<String>,
+    pub tag: Option<String>,
+    pub is_pr: bool,
+    pub pr_branch: Option<String>,
+    pub commit: Option<String>,
+    pub build_url: Option<String>,
+    pub job: Option<String>,
+}
+
+/// Detect the CI environment from the process environment variables.
+pub fn detect_ci_env() -> CiEnv {
+    detect(&std::env::vars().collect())
+}
+
+/// Detect the CI environment from the given environment variables.
+///
+/// # Example
+///
+/// ```
+/// # use std::collections::HashMap;
+/// # use semantic_release::ci::{detect, CiService};
+/// let env = HashMap::from([
+///     ("GITLAB_CI".to_string(), "true".to_string()),
+///     ("CI_COMMIT_REF_NAME".to_string(), "main".to_string()),
+/// ]);
+///
+/// let ci_env = detect(&env);
+///
+/// assert_eq!(ci_env.service, Some(CiService::GitLab));
+/// assert_eq!(ci_env.branch.as_deref(), Some("main"));
+/// ```
+pub fn detect(env: &HashMap<String, String>) -> CiEnv {
+    let var = |name: &str| env.get(name).filter(|value| !value.is_empty()).cloned();
+    let has = |name: &str| var(name).is_some();
+    let is = |name: &str, value: &str| var(name).as_deref() == Some(value);
+
+    let ci_env = |service,
+                  is_pr: bool,
+                  branch: Option<String>,
+                  pr_branch: Option<String>,
+                  commit,
+                  build_url,
+                  job| {
+        let mut ci_env = CiEnv {
+            is_ci: true,
+            service: Some(service),
+            is_pr,
+            commit,
+            build_url,
+            job,
+            ..CiEnv::default()
+        };
+
+        match branch.as_deref().map(parse_ref) {
+            Some(GitRef::Branch(name)) => ci_env.branch = Some(name),
+            Some(GitRef::Tag(name)) => ci_env.tag = Some(name),
+            Some(GitRef::PullRequest(_)) => ci_env.is_pr = true,
+            None => {}
+        }
+
+        if ci_env.is_pr {
+            ci_env.pr_branch = match pr_branch.as_deref().map(parse_ref) {
+                Some(GitRef::Branch(name)) => Some(name),
+                _ => None,
+            };
+        }
+
+        ci_env
+    };
+
+    if has("GITHUB_ACTIONS") {
+        let is_pr = is("GITHUB_EVENT_NAME", "pull_request")
+            || is("GITHUB_EVENT_NAME", "pull_request_target");
+
+        let build_url = match (
+            var("GITHUB_SERVER_URL"),
+            var("GITHUB_REPOSITORY"),
+            var("GITHUB_RUN_ID"),
+        ) {
+            (Some(server), Some(repository), Some(run_id)) => {
+                Some(format!("{}/{}/actions/runs/{}", server, repository, run_id))
+            }
+            _ => None,
+        };
+
+        return ci_env(
+            CiService::GitHubActions,
+            is_pr,
+            if is_pr {
+                var("GITHUB_BASE_REF")
+            } else {
+                var("GITHUB_REF")
+            },
+            var("GITHUB_HEAD_REF"),
+            var("GITHUB_SHA"),
+            build_url,
+            var("GITHUB_JOB"),
+        );
+    }
+
+    if has("GITLAB_CI") {
+        let is_pr = has("CI_MERGE_REQUEST_ID");
+
+        let branch = if is_pr {
+            var("CI_MERGE_REQUEST_TARGET_BRANCH_NAME")
+        } else if let Some(tag) = var("CI_COMMIT_TAG") {
+            Some(format!("refs/tags/{}", tag))
+        } else {
+            var("CI_COMMIT_REF_NAME")
+        };
+
+        return ci_env(
+            CiService::GitLab,
+            is_pr,
+            branch,
+            var("CI_MERGE_REQUEST_SOURCE_BRANCH_NAME"),
+            var("CI_COMMIT_SHA"),
+            var("CI_PIPELINE_URL"),
+            var("CI_JOB_ID"),
+        );
+    }
+
+    if has("CIRCLECI") {
+        let is_pr = has("CIRCLE_PULL_REQUEST") || has("CIRCLE_PR_NUMBER");
+
+        return ci_env(
+            CiService::CircleCi,
+            is_pr,
+            if is_pr { None } else { var("CIRCLE_BRANCH") },
+            var("CIRCLE_BRANCH"),
+            var("CIRCLE_SHA1"),
+            var("CIRCLE_BUILD_URL"),
+            var("CIRCLE_JOB"),
+        );
+    }
+
+    if has("TRAVIS") {
+        let is_pr = has("TRAVIS_PULL_REQUEST") && !is("TRAVIS_PULL_REQUEST", "false");
+
+        return ci_env(
+            CiService::Travis,
+            is_pr,
+            var("TRAVIS_BRANCH"),
+            var("TRAVIS_PULL_REQUEST_BRANCH"),
+            var("TRAVIS_COMMIT"),
+            var("TRAVIS_BUILD_WEB_URL"),
+            var("TRAVIS_JOB_NUMBER"),
+        );
+    }
+
+    if has("JENKINS_URL") {
+        let is_pr = has("ghprbPullId") || has("gitlabMergeRequestId") || has("CHANGE_ID");
+
+        let branch = ["ghprbTargetBranch", "gitlabTargetBranch", "CHANGE_TARGET"]
+            .iter()
+            .filter(|_| is_pr)
+            .chain(["GIT_LOCAL_BRANCH", "GIT_BRANCH", "BRANCH_NAME"].iter())
+            .find_map(|name| var(name));
+
+        let pr_branch = ["ghprbSourceBranch", "gitlabSourceBranch", "CHANGE_BRANCH"]
+            .iter()
+            .find_map(|name| var(name));
+
+        return ci_env(
+            CiService::Jenkins,
+            is_pr,
+            branch,
+            pr_branch,
+            var("ghprbActualCommit").or_else(|| var("GIT_COMMIT")),
+            var("BUILD_URL"),
+            var("BUILD_NUMBER"),
+        );
+    }
+
+    if has("BUILD_BUILDURI") {
+        let is_pr = has("SYSTEM_PULLREQUEST_PULLREQUESTID");
+
+        let build_url = match (
+            var("SYSTEM_TEAMFOUNDATIONSERVERURI"),
+            var("SYSTEM_TEAMPROJECT"),
+            var("BUILD_BUILDID"),
+        ) {
+            (Some(server), Some(project), Some(build_id)) => Some(format!(
+                "{}{}/_build/results?buildId={}",
+                server, project, build_id
+            )),
+            _ => None,
+        };
+
+        return ci_env(
+            CiService::AzurePipelines,
+            is_pr,
+            if is_pr {
+                var("SYSTEM_PULLREQUEST_TARGETBRANCH")
+            } else {
+                var("BUILD_SOURCEBRANCH")
+            },
+            var("SYSTEM_PULLREQUEST_SOURCEBRANCH"),
+            var("BUILD_SOURCEVERSION"),
+            build_url,
+            var("SYSTEM_JOBID"),
+        );
+    }
+
+    if has("BUILDKITE") {
+        let is_pr = has("BUILDKITE_PULL_REQUEST") && !is("BUILDKITE_PULL_REQUEST", "false");
+
+        return ci_env(
+            CiService::Buildkite,
+            is_pr,
+            if is_pr {
+                var("BUILDKITE_PULL_REQUEST_BASE_BRANCH")
+            } else {
+                var("BUILDKITE_BRANCH")
+            },
+            var("BUILDKITE_BRANCH"),
+            var("BUILDKITE_COMMIT"),
+            var("BUILDKITE_BUILD_URL"),
+            var("BUILDKITE_JOB_ID"),
+        );
+    }
+
+    if has("DRONE") {
+        let is_pr = is("DRONE_BUILD_EVENT", "pull_request");
+
+        return ci_env(
+            CiService::Drone,
+            is_pr,
+            if is_pr {
+                var("DRONE_TARGET_BRANCH")
+            } else {
+                var("DRONE_BRANCH")
+            },
+            var("DRONE_SOURCE_BRANCH"),
+            var("DRONE_COMMIT_SHA"),
+            var("DRONE_BUILD_LINK"),
+            var("DRONE_STEP_NUMBER"),
+        );
+    }
+
+    if is("CI", "woodpecker") {
+        let is_pr = is("CI_PIPELINE_EVENT", "pull_request");
+
+        return ci_env(
+            CiService::Woodpecker,
+            is_pr,
+            if is_pr {
+                var("CI_COMMIT_TARGET_BRANCH")
+            } else {
+                var("CI_COMMIT_BRANCH")
+            },
+            var("CI_COMMIT_SOURCE_BRANCH"),
+            var("CI_COMMIT_SHA"),
+            var("CI_PIPELINE_URL"),
+            var("CI_STEP_NUMBER"),
+        );
+    }
+
+    if has("BITBUCKET_BUILD_NUMBER") {
+        let is_pr = has("BITBUCKET_PR_ID");
+
+        let build_url = var("BITBUCKET_REPO_FULL_NAME").map(|repository| {
+            format!(
+                "https://bitbucket.org/{}/addon/pipelines/home#!/results/{}",
+                repository, env["BITBUCKET_BUILD_NUMBER"]
+            )
+        });
+
+        return ci_env(
+            CiService::BitbucketPipelines,
+            is_pr,
+            if is_pr {
+                var("BITBUCKET_PR_DESTINATION_BRANCH")
+            } else {
+                var("BITBUCKET_BRANCH")
+            },
+            var("BITBUCKET_BRANCH"),
+            var("BITBUCKET_COMMIT"),
+            build_url,
+            var("BITBUCKET_STEP_UUID"),
+        );
+    }
+
+    if has("TEAMCITY_VERSION") {
+        // TeamCity exposes the branch only through its build properties files.
+        let build_properties = var("TEAMCITY_BUILD_PROPERTIES_FILE")
+            .map(|path| read_properties(&path))
+            .unwrap_or_default();
+
+        let config_properties = build_properties
+            .get("teamcity.configuration.properties.file")
+            .map(|path| read_properties(path))
+            .unwrap_or_default();
+
+        let branch = config_properties
+            .get("teamcity.build.branch")
+            .or_else(|| build_properties.get("teamcity.build.branch"))
+            .cloned();
+
+        return ci_env(
+            CiService::TeamCity,
+            false,
+            branch,
+            None,
+            var("BUILD_VCS_NUMBER"),
+            None,
+            var("BUILD_NUMBER"),
+        );
+    }
+
+    if is("CI", "true") {
+        return ci_env(CiService::Generic, false, None, None, None, None, None);
+    }
+
+    CiEnv::default()
+}
+
+/// Read a Java `.properties` file as written by TeamCity, ignoring it if it can't be read.
+fn read_properties(path: &str) -> HashMap<String, String> {
+    let contents = fs::read_to_string(path).unwrap_or_default();
+
+    contents
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#') && !line.starts_with('!'))
+        .filter_map(|line| line.split_once(['=', ':']))
+        .map(|(key, value)| (key.trim().to_string(), value.trim().replace('\\', "")))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn env(vars: &[(&str, &str)]) -> HashMap<String, String> {
+        vars.iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_no_ci() {
+        assert_eq!(detect(&env(&[])), CiEnv::default());
+        assert!(!detect(&env(&[("CI", "false")])).is_ci);
+    }
+
+    #[test]
+    fn test_generic_ci() {
+        let ci_env = detect(&env(&[("CI", "true")]));
+
+        assert!(ci_env.is_ci);
+        assert_eq!(ci_env.service, Some(CiService::Generic));
+        assert_eq!(ci_env.branch, None);
+    }
+
+    #[test]
+    fn test_github_actions_push() {
+        let ci_env = detect(&env(&[
+            ("GITHUB_ACTIONS", "true"),
+            ("GITHUB_EVENT_NAME", "push"),
+            ("GITHUB_REF", "refs/heads/main"),
+            ("GITHUB_SHA", "abc123"),
+            ("GITHUB_SERVER_URL", "https://github.com"),
+            ("GITHUB_REPOSITORY", "owner/repo"),
+            ("GITHUB_RUN_ID", "42"),
+        ]));
+
+        assert_eq!(
+            ci_env,
+            CiEnv {
+                is_ci: true,
+                service: Some(CiService::GitHubActions),
+                branch: Some("main".to_string()),
+                tag: None,
+                is_pr: false,
+                pr_branch: None,
+                commit: Some("abc123".to_string()),
+                build_url: Some("https://github.com/owner/repo/actions/runs/42".to_string()),
+                job: None,
+            }
+        );
+    }
+
+    #[test]
+    fn test_github_actions_pull_request() {
+        let ci_env = detect(&env(&[
+            ("GITHUB_ACTIONS", "true"),
+            ("GITHUB_EVENT_NAME", "pull_request"),
+            ("GITHUB_REF", "refs/pull/7/merge"),
+            ("GITHUB_BASE_REF", "main"),
+            ("GITHUB_HEAD_REF", "feature"),
+        ]));
+
+        assert!(ci_env.is_pr);
+        assert_eq!(ci_env.branch.as_deref(), Some("main"));
+        assert_eq!(ci_env.pr_branch.as_deref(), Some("feature"));
+    }
+
+    #[test]
+    fn test_github_actions_tag() {
+        let ci_env = detect(&env(&[
+            ("GITHUB_ACTIONS", "true"),
+            ("GITHUB_EVENT_NAME", "push"),
+            ("GITHUB_REF", "refs/tags/v1.2.0"),
+        ]));
+
+        assert_eq!(ci_env.branch, None);
+        assert_eq!(ci_env.tag.as_deref(), Some("v1.2.0"));
+    }
+
+    #[test]
+    fn test_parse_ref() {
+        assert_eq!(parse_ref("main"), GitRef::Branch("main".to_string()));
+        assert_eq!(
+            parse_ref("refs/heads/release/1.x"),
+            GitRef::Branch("release/1.x".to_string())
+        );
+        assert_eq!(
+            parse_ref("refs/remotes/origin/next"),
+            GitRef::Branch("next".to_string())
+        );
+        assert_eq!(
+            parse_ref("refs/merge-requests/12/head"),
+            GitRef::PullRequest(12)
+        );
+    }
+
+    #[test]
+    fn test_gitlab_merge_request() {
+        let ci_env = detect(&env(&[
+            ("GITLAB_CI", "true"),
+            ("CI", "true"),
+            ("CI_MERGE_REQUEST_ID", "3"),
+            ("CI_MERGE_REQUEST_TARGET_BRANCH_NAME", "main"),
+            ("CI_MERGE_REQUEST_SOURCE_BRANCH_NAME", "feature"),
+            ("CI_COMMIT_REF_NAME", "feature"),
+        ]));
+
+        assert_eq!(ci_env.service, Some(CiService::GitLab));
+        assert!(ci_env.is_pr);
+        assert_eq!(ci_env.branch.as_deref(), Some("main"));
+        assert_eq!(ci_env.pr_branch.as_deref(), Some("feature"));
+    }
+
+    #[test]
+    fn test_travis_push_is_not_pr() {
+        let ci_env = detect(&env(&[
+            ("TRAVIS", "true"),
+            ("TRAVIS_PULL_REQUEST", "false"),
+            ("TRAVIS_BRANCH", "next"),
+        ]));
+
+        assert_eq!(ci_env.service, Some(CiService::Travis));
+        assert!(!ci_env.is_pr);
+        assert_eq!(ci_env.branch.as_deref(), Some("next"));
+        assert_eq!(ci_env.pr_branch, None);
+    }
+
+    #[test]
+    fn test_woodpecker_is_not_generic() {
+        let ci_env = detect(&env(&[
+            ("CI", "woodpecker"),
+            ("CI_PIPELINE_EVENT", "push"),
+            ("CI_COMMIT_BRANCH", "main"),
+        ]));
+
+        assert_eq!(ci_env.service, Some(CiService::Woodpecker));
+        assert_eq!(ci_env.branch.as_deref(), Some("main"));
+    }
+}
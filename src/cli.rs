@@ -0,0 +1,138 @@
+use std::path::PathBuf;
+
+use clap::{Parser, Subcommand, ValueEnum};
+
+use crate::get_config::Config;
+
+/// Command line options.
+///
+/// Options given on the command line take precedence over the config file,
+/// which takes precedence over the defaults.
+#[derive(Debug, Default, Parser)]
+#[command(
+    name = "semantic-release",
+    version,
+    about = "Fully automated version management and package publishing",
+    long_about = None
+)]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
+    /// Skip the publish steps and only report what the next release would be
+    #[arg(short, long)]
+    pub dry_run: bool,
+
+    /// Skip the Continuous Integration environment verifications
+    #[arg(long)]
+    pub no_ci: bool,
+
+    /// Output debugging information
+    #[arg(long)]
+    pub debug: bool,
+
+    /// Git branches to release from, comma separated
+    #[arg(short, long, value_delimiter = ',', num_args = 1..)]
+    pub branches: Option<Vec<String>>,
+
+    /// Git repository URL
+    #[arg(short, long)]
+    pub repository_url: Option<String>,
+
+    /// Git tag format, must contain `${version}`
+    #[arg(short, long)]
+    pub tag_format: Option<String>,
+
+    /// Plugins, comma separated
+    #[arg(short, long, value_delimiter = ',', num_args = 1..)]
+    pub plugins: Option<Vec<String>>,
+
+    /// Shareable configurations, comma separated, replacing the `extends` of the config file
+    #[arg(short, long, value_delimiter = ',', num_args = 1..)]
+    pub extends: Option<Vec<String>>,
+
+    /// Path to the config file, instead of looking for one
+    #[arg(long, value_name = "PATH")]
+    pub config: Option<PathBuf>,
+
+    /// Format of the release results printed on stdout
+    #[arg(long, value_enum, default_value_t)]
+    pub output: OutputFormat,
+
+    /// Append the release results to a dotenv file, e.g. for GitLab CI, without the multiline release notes
+    #[arg(long, value_name = "PATH")]
+    pub output_file: Option<PathBuf>,
+}
+
+/// Formats of the release results printed on stdout.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    /// Human readable messages
+    #[default]
+    Text,
+    /// A JSON object, with the messages printed on stderr
+    Json,
+}
+
+/// Commands run instead of a release.
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Check a config file against the schema and the release rules, reporting every problem
+    Validate {
+        /// Path to the config file, instead of looking for one
+        #[arg(value_name = "PATH")]
+        path: Option<PathBuf>,
+    },
+    /// Print the JSON Schema of the config files
+    Schema,
+}
+
+impl Cli {
+    /// Override the options of `config` that were given on the command line.
+    ///
+    /// `--config` and `--extends` are given to [`load_config`](crate::get_config::load_config)
+    /// instead, as they change which configs are merged.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use clap::{Parser, Subcommand, ValueEnum};
+    /// # use semantic_release::{cli::Cli, get_config::Config};
+    /// let mut config = Config::default();
+    /// let cli = Cli::parse_from(["semantic-release", "--no-ci", "--branches", "main,next"]);
+    ///
+    /// cli.apply(&mut config);
+    ///
+    /// assert!(!config.ci);
+    /// assert_eq!(config.branches, vec!["main", "next"]);
+    /// ```
+    pub fn apply(&self, config: &mut Config) {
+        if self.dry_run {
+            config.dry_run = true;
+        }
+
+        if self.no_ci {
+            config.ci = false;
+        }
+
+        if self.debug {
+            config.debug = true;
+        }
+
+        if let Some(branches) = &self.branches {
+            config.branches = branches.clone();
+        }
+
+        if let Some(repository_url) = &self.repository_url {
+            config.repository_url = repository_url.clone();
+        }
+
+        if let Some(tag_format) = &self.tag_format {
+            config.tag_format = tag_format.clone();
+        }
+
+        if let Some(plugins) = &self.plugins {
+            config.plugins = plugins.clone();
+        }
+    }
+}
@@ -0,0 +1,225 @@
+use std::collections::HashMap;
+
+use regex::{Captures, Regex};
+use serde_json::{Map, Value};
+
+/// Prefix of the environment variables overriding config options.
+pub const ENV_PREFIX: &str = "SEMANTIC_RELEASE_";
+
+/// Separator between the keys of nested options in override variable names.
+const NESTING_SEPARATOR: &str = "__";
+
+/// Expand `${VAR}` and `${VAR:-default}` in every string of a config value.
+///
+/// Only upper case variable names are expanded so template variables such as the
+/// `${version}` of `tag_format` are kept. Undefined variables expand to their default,
+/// or to an empty string without one.
+///
+/// Returns the undefined variables without a default, sorted and without duplicates.
+///
+/// # Example
+///
+/// ```
+/// # use std::collections::HashMap;
+/// # use semantic_release::config_env::expand_env_vars;
+/// # use serde_json::json;
+/// let env = HashMap::from([("CI_REPOSITORY_URL".to_string(), "https://example.com/repo.git".to_string())]);
+/// let mut config = json!({ "repository_url": "${CI_REPOSITORY_URL}", "tag_format": "${PREFIX:-v}${version}" });
+///
+/// let undefined = expand_env_vars(&mut config, &env);
+///
+/// assert_eq!(config, json!({ "repository_url": "https://example.com/repo.git", "tag_format": "v${version}" }));
+/// assert!(undefined.is_empty());
+/// ```
+pub fn expand_env_vars(value: &mut Value, env: &HashMap<String, String>) -> Vec<String> {
+    let re =
+        Regex::new(r"\$\{([A-Z_][A-Z0-9_]*)(?::-([^}]*))?\}").expect("should be a valid regex");
+
+    let mut undefined = vec![];
+
+    expand(value, &re, env, &mut undefined);
+
+    undefined.sort();
+    undefined.dedup();
+
+    undefined
+}
+
+fn expand(
+    value: &mut Value,
+    re: &Regex,
+    env: &HashMap<String, String>,
+    undefined: &mut Vec<String>,
+) {
+    match value {
+        Value::String(string) => {
+            let expanded = re.replace_all(string, |caps: &Captures| {
+                match (env.get(&caps[1]), caps.get(2)) {
+                    (Some(value), _) => value.clone(),
+                    (None, Some(default)) => default.as_str().to_string(),
+                    (None, None) => {
+                        undefined.push(caps[1].to_string());
+
+                        String::new()
+                    }
+                }
+            });
+
+            *string = expanded.into_owned();
+        }
+        Value::Array(values) => values
+            .iter_mut()
+            .for_each(|value| expand(value, re, env, undefined)),
+        Value::Object(options) => options
+            .values_mut()
+            .for_each(|value| expand(value, re, env, undefined)),
+        _ => {}
+    }
+}
+
+/// Apply the `SEMANTIC_RELEASE_*` environment variables to a config value.
+///
+/// The rest of the variable name is the lower case option name, with `__` between
+/// the keys of nested options, e.g. `SEMANTIC_RELEASE_TAG_FORMAT` or
+/// `SEMANTIC_RELEASE_SECRETS__NAMES`. Values are used as is for string options, and
+/// parsed as JSON otherwise. Array options also accept comma separated values.
+///
+/// Returns the top-level options that were overridden, along with the variable overriding them.
+///
+/// # Example
+///
+/// ```
+/// # use std::collections::HashMap;
+/// # use semantic_release::config_env::apply_env_overrides;
+/// # use serde_json::json;
+/// let env = HashMap::from([
+///     ("SEMANTIC_RELEASE_BRANCHES".to_string(), "main, next".to_string()),
+///     ("SEMANTIC_RELEASE_DRY_RUN".to_string(), "true".to_string()),
+/// ]);
+/// let mut config = json!({ "branches": ["main"], "dry_run": false });
+///
+/// apply_env_overrides(&mut config, &env);
+///
+/// assert_eq!(config, json!({ "branches": ["main", "next"], "dry_run": true }));
+/// ```
+pub fn apply_env_overrides(
+    value: &mut Value,
+    env: &HashMap<String, String>,
+) -> Vec<(String, String)> {
+    let mut overrides = env
+        .iter()
+        .filter_map(|(name, override_value)| {
+            let option = name.strip_prefix(ENV_PREFIX)?;
+
+            (!option.is_empty()).then_some((name, option.to_lowercase(), override_value))
+        })
+        .collect::<Vec<_>>();
+
+    // Apply parent options first so nested overrides are kept.
+    overrides.sort();
+
+    let mut applied = vec![];
+
+    for (name, option, override_value) in overrides {
+        let keys: Vec<&str> = option.split(NESTING_SEPARATOR).collect();
+
+        if keys.iter().any(|key| key.is_empty()) {
+            continue;
+        }
+
+        if let Some(target) = option_mut(value, &keys) {
+            *target = parse_override(target, override_value);
+
+            applied.push((keys[0].to_string(), name.clone()));
+        }
+    }
+
+    applied
+}
+
+/// Get the option at `keys`, creating the missing tables on the way.
+fn option_mut<'a>(value: &'a mut Value, keys: &[&str]) -> Option<&'a mut Value> {
+    let mut current = value;
+
+    for key in keys {
+        if current.is_null() {
+            *current = Value::Object(Map::new());
+        }
+
+        current = current
+            .as_object_mut()?
+            .entry(key.to_string())
+            .or_insert(Value::Null);
+    }
+
+    Some(current)
+}
+
+fn parse_override(current: &Value, override_value: &str) -> Value {
+    match current {
+        Value::String(_) => Value::String(override_value.to_string()),
+        Value::Array(_) if !override_value.trim_start().starts_with('[') => Value::Array(
+            override_value
+                .split(',')
+                .map(str::trim)
+                .filter(|item| !item.is_empty())
+                .map(|item| Value::String(item.to_string()))
+                .collect(),
+        ),
+        _ => serde_json::from_str(override_value)
+            .unwrap_or_else(|_| Value::String(override_value.to_string())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn env(vars: &[(&str, &str)]) -> HashMap<String, String> {
+        vars.iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_expand_env_vars() {
+        let mut config = json!({
+            "branches": ["${RELEASE_BRANCH}", "${MISSING}", "${lower}"],
+            "tag_format": "${TAG_PREFIX:-v}${version}",
+        });
+
+        let undefined = expand_env_vars(&mut config, &env(&[("RELEASE_BRANCH", "main")]));
+
+        assert_eq!(
+            config,
+            json!({ "branches": ["main", "", "${lower}"], "tag_format": "v${version}" })
+        );
+        assert_eq!(undefined, vec!["MISSING"]);
+    }
+
+    #[test]
+    fn test_nested_overrides() {
+        let mut config = json!({ "branches": ["main"], "ci": true });
+
+        let applied = apply_env_overrides(
+            &mut config,
+            &env(&[
+                ("SEMANTIC_RELEASE_BRANCHES", "[\"main\", \"beta\"]"),
+                ("SEMANTIC_RELEASE_CI", "false"),
+                ("SEMANTIC_RELEASE_SECRETS__DETECT", "false"),
+                ("SEMANTIC_RELEASE_", "ignored"),
+            ]),
+        );
+
+        assert_eq!(
+            config,
+            json!({
+                "branches": ["main", "beta"],
+                "ci": false,
+                "secrets": { "detect": false },
+            })
+        );
+        assert_eq!(applied.len(), 3);
+    }
+}
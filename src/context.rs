@@ -1,9 +1,14 @@
-use crate::get_config::Config;
+use std::path::PathBuf;
+
+use crate::{ci::CiEnv, get_config::Config, logger::Logger};
 
 #[derive(Debug)]
 pub struct Context {
+    pub cwd: PathBuf,
     pub is_ci: bool,
     pub is_pr: bool,
     pub branch: String,
     pub config: Config,
+    pub ci_env: CiEnv,
+    pub logger: Logger,
 }
@@ -0,0 +1,388 @@
+use std::{
+    collections::HashSet,
+    fs,
+    io::Write,
+    path::PathBuf,
+    process::{Command, Stdio},
+};
+
+use git2::{Cred, CredentialType, ErrorClass, ErrorCode, RemoteCallbacks};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+/// Environment variables holding a forge token, paired with the username
+/// each forge expects for token based HTTP authentication.
+const TOKEN_ENV_VARS: [(&str, &str); 6] = [
+    ("GH_TOKEN", "x-access-token"),
+    ("GITHUB_TOKEN", "x-access-token"),
+    ("GL_TOKEN", "gitlab-ci-token"),
+    ("GITLAB_TOKEN", "gitlab-ci-token"),
+    ("BB_TOKEN", "x-token-auth"),
+    ("BITBUCKET_TOKEN", "x-token-auth"),
+];
+
+/// The credential providers that can be enabled in `release.toml` through the
+/// `credentials` key. They are tried in the order they are listed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum CredentialKind {
+    /// Keys loaded in the running `ssh-agent`.
+    SshAgent,
+    /// Private key file from `GIT_SSH_KEY_PATH`, decrypted with `GIT_SSH_KEY_PASSPHRASE`.
+    SshKey,
+    /// Credentials returned by `git credential fill`.
+    CredentialHelper,
+    /// `GIT_CREDENTIALS` (`user:token`) or a forge token such as `GITHUB_TOKEN`.
+    Token,
+    /// Matching `machine` entry of `$NETRC` or `~/.netrc`.
+    Netrc,
+}
+
+/// Default order in which credential providers are tried.
+pub fn default_credentials() -> Vec<CredentialKind> {
+    vec![
+        CredentialKind::SshAgent,
+        CredentialKind::SshKey,
+        CredentialKind::CredentialHelper,
+        CredentialKind::Token,
+        CredentialKind::Netrc,
+    ]
+}
+
+/// A source of credentials for libgit2 network operations.
+///
+/// Returning `None` means the provider has nothing to offer for this request and the next provider should be tried.
+pub trait CredentialProvider {
+    fn name(&self) -> &'static str;
+
+    fn credentials(
+        &self,
+        url: &str,
+        username: Option<&str>,
+        allowed: CredentialType,
+    ) -> Option<Result<Cred, git2::Error>>;
+}
+
+impl CredentialKind {
+    pub fn provider(&self) -> Box<dyn CredentialProvider> {
+        match self {
+            CredentialKind::SshAgent => Box::new(SshAgent),
+            CredentialKind::SshKey => Box::new(SshKey),
+            CredentialKind::CredentialHelper => Box::new(CredentialHelper),
+            CredentialKind::Token => Box::new(Token),
+            CredentialKind::Netrc => Box::new(Netrc),
+        }
+    }
+}
+
+pub struct SshAgent;
+
+impl CredentialProvider for SshAgent {
+    fn name(&self) -> &'static str {
+        "ssh-agent"
+    }
+
+    fn credentials(
+        &self,
+        _url: &str,
+        username: Option<&str>,
+        allowed: CredentialType,
+    ) -> Option<Result<Cred, git2::Error>> {
+        if !allowed.contains(CredentialType::SSH_KEY) {
+            return None;
+        }
+
+        Some(Cred::ssh_key_from_agent(username.unwrap_or("git")))
+    }
+}
+
+pub struct SshKey;
+
+impl CredentialProvider for SshKey {
+    fn name(&self) -> &'static str {
+        "ssh-key"
+    }
+
+    fn credentials(
+        &self,
+        _url: &str,
+        username: Option<&str>,
+        allowed: CredentialType,
+    ) -> Option<Result<Cred, git2::Error>> {
+        if !allowed.contains(CredentialType::SSH_KEY) {
+            return None;
+        }
+
+        let key_path = PathBuf::from(std::env::var("GIT_SSH_KEY_PATH").ok()?);
+        let passphrase = std::env::var("GIT_SSH_KEY_PASSPHRASE").ok();
+        let public_key = key_path.with_extension("pub");
+
+        Some(Cred::ssh_key(
+            username.unwrap_or("git"),
+            public_key.is_file().then_some(public_key.as_path()),
+            &key_path,
+            passphrase.as_deref(),
+        ))
+    }
+}
+
+pub struct CredentialHelper;
+
+impl CredentialProvider for CredentialHelper {
+    fn name(&self) -> &'static str {
+        "git credential fill"
+    }
+
+    fn credentials(
+        &self,
+        url: &str,
+        username: Option<&str>,
+        allowed: CredentialType,
+    ) -> Option<Result<Cred, git2::Error>> {
+        if !allowed.contains(CredentialType::USER_PASS_PLAINTEXT) {
+            return None;
+        }
+
+        let url = Url::parse(url).ok()?;
+
+        let mut request = format!(
+            "protocol={}\nhost={}\n",
+            url.scheme(),
+            url.host_str().unwrap_or_default()
+        );
+
+        let path = url.path().trim_start_matches('/');
+
+        if !path.is_empty() {
+            request.push_str(&format!("path={}\n", path));
+        }
+
+        if let Some(username) = username {
+            request.push_str(&format!("username={}\n", username));
+        }
+
+        request.push('\n');
+
+        let mut child = Command::new("git")
+            .args(["credential", "fill"])
+            .env("GIT_TERMINAL_PROMPT", "0")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .ok()?;
+
+        child.stdin.take()?.write_all(request.as_bytes()).ok()?;
+
+        let output = child.wait_with_output().ok()?;
+
+        if !output.status.success() {
+            return None;
+        }
+
+        let stdout = String::from_utf8(output.stdout).ok()?;
+
+        let mut helper_username = None;
+        let mut helper_password = None;
+
+        for line in stdout.lines() {
+            if let Some(value) = line.strip_prefix("username=") {
+                helper_username = Some(value.to_string());
+            } else if let Some(value) = line.strip_prefix("password=") {
+                helper_password = Some(value.to_string());
+            }
+        }
+
+        Some(Cred::userpass_plaintext(
+            &helper_username?,
+            &helper_password?,
+        ))
+    }
+}
+
+pub struct Token;
+
+impl CredentialProvider for Token {
+    fn name(&self) -> &'static str {
+        "token"
+    }
+
+    fn credentials(
+        &self,
+        _url: &str,
+        _username: Option<&str>,
+        allowed: CredentialType,
+    ) -> Option<Result<Cred, git2::Error>> {
+        if !allowed.contains(CredentialType::USER_PASS_PLAINTEXT) {
+            return None;
+        }
+
+        if let Ok(credentials) = std::env::var("GIT_CREDENTIALS") {
+            if let Some((username, password)) = credentials.split_once(':') {
+                return Some(Cred::userpass_plaintext(username, password));
+            }
+        }
+
+        TOKEN_ENV_VARS.iter().find_map(|(env_var, username)| {
+            std::env::var(env_var)
+                .ok()
+                .filter(|token| !token.is_empty())
+                .map(|token| Cred::userpass_plaintext(username, &token))
+        })
+    }
+}
+
+pub struct Netrc;
+
+impl CredentialProvider for Netrc {
+    fn name(&self) -> &'static str {
+        "netrc"
+    }
+
+    fn credentials(
+        &self,
+        url: &str,
+        _username: Option<&str>,
+        allowed: CredentialType,
+    ) -> Option<Result<Cred, git2::Error>> {
+        if !allowed.contains(CredentialType::USER_PASS_PLAINTEXT) {
+            return None;
+        }
+
+        let host = Url::parse(url).ok()?.host_str()?.to_string();
+
+        let path = match std::env::var("NETRC") {
+            Ok(path) => PathBuf::from(path),
+            Err(_) => PathBuf::from(std::env::var("HOME").ok()?).join(".netrc"),
+        };
+
+        let contents = fs::read_to_string(path).ok()?;
+
+        let (login, password) = find_netrc_entry(&contents, &host)?;
+
+        Some(Cred::userpass_plaintext(&login, &password))
+    }
+}
+
+/// Find the `login` and `password` of the `machine` entry matching `host`, falling back to the `default` entry.
+fn find_netrc_entry(contents: &str, host: &str) -> Option<(String, String)> {
+    // Each entry is `(machine, login, password)`, `machine` being `None` for the `default` entry.
+    let mut entries: Vec<(Option<&str>, Option<&str>, Option<&str>)> = Vec::new();
+
+    let mut tokens = contents.split_whitespace();
+
+    while let Some(token) = tokens.next() {
+        match token {
+            "machine" => entries.push((tokens.next(), None, None)),
+            "default" => entries.push((None, None, None)),
+            "login" | "password" => {
+                let value = tokens.next();
+
+                if let Some(entry) = entries.last_mut() {
+                    if token == "login" {
+                        entry.1 = value;
+                    } else {
+                        entry.2 = value;
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let to_credentials = |&(_, login, password): &(Option<&str>, Option<&str>, Option<&str>)| {
+        Some((login?.to_string(), password?.to_string()))
+    };
+
+    entries
+        .iter()
+        .find(|(machine, _, _)| *machine == Some(host))
+        .or_else(|| entries.iter().find(|(machine, _, _)| machine.is_none()))
+        .and_then(to_credentials)
+}
+
+/// Build the remote callbacks used by every network operation, trying the
+/// configured credential providers in order.
+///
+/// libgit2 calls the credentials callback again every time the returned
+/// credentials are rejected, so each provider is only tried once.
+///
+/// # Example
+///
+/// ```
+/// use semantic_release::credentials::{default_credentials, remote_callbacks};
+///
+/// let callbacks = remote_callbacks(&default_credentials());
+/// ```
+pub fn remote_callbacks<'a>(kinds: &[CredentialKind]) -> RemoteCallbacks<'a> {
+    let providers: Vec<Box<dyn CredentialProvider>> =
+        kinds.iter().map(|kind| kind.provider()).collect();
+
+    let mut attempted: HashSet<usize> = HashSet::new();
+
+    let mut callbacks = RemoteCallbacks::new();
+
+    callbacks.credentials(move |url, username, allowed| {
+        if allowed.contains(CredentialType::USERNAME) {
+            return Cred::username(username.unwrap_or("git"));
+        }
+
+        for (index, provider) in providers.iter().enumerate() {
+            if attempted.contains(&index) {
+                continue;
+            }
+
+            if let Some(result) = provider.credentials(url, username, allowed) {
+                attempted.insert(index);
+
+                match result {
+                    Ok(cred) => return Ok(cred),
+                    Err(_) => continue,
+                }
+            }
+        }
+
+        if allowed.contains(CredentialType::DEFAULT) {
+            return Cred::default();
+        }
+
+        Err(git2::Error::new(
+            ErrorCode::Auth,
+            ErrorClass::Callback,
+            "no valid credentials available",
+        ))
+    });
+
+    callbacks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_netrc_entry() {
+        let contents = "machine gitlab.com login gl password glpass\nmachine github.com\n  login gh\n  password ghpass\ndefault login anon password anonpass";
+
+        assert_eq!(
+            find_netrc_entry(contents, "github.com"),
+            Some(("gh".to_string(), "ghpass".to_string()))
+        );
+        assert_eq!(
+            find_netrc_entry(contents, "gitlab.com"),
+            Some(("gl".to_string(), "glpass".to_string()))
+        );
+        assert_eq!(
+            find_netrc_entry(contents, "bitbucket.org"),
+            Some(("anon".to_string(), "anonpass".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_find_netrc_entry_without_match() {
+        let contents = "machine gitlab.com login gl password glpass";
+
+        assert_eq!(find_netrc_entry(contents, "github.com"), None);
+    }
+}
@@ -0,0 +1,413 @@
+use std::{
+    error::Error,
+    fmt::{self, Display, Formatter},
+    path::PathBuf,
+};
+
+const DOCS_URL: &str = "https://github.com/semantic-release/semantic-release/blob/master/docs";
+
+/// Errors reported to the user, mirroring the `get-error` definitions of semantic-release.
+///
+/// Each error has a stable code, a one line message and Markdown details explaining how to fix it.
+#[derive(Debug)]
+pub enum SemanticReleaseError {
+    /// The command is not executed from a git repository.
+    NoGitRepo { cwd: PathBuf },
+    /// The repository URL couldn't be determined.
+    NoRepoUrl,
+    /// The repository URL isn't a URL, an scp-like address or an absolute path Git can push to.
+    InvalidRepoUrl {
+        repository_url: String,
+        reason: String,
+    },
+    /// The remote repository doesn't allow pushing to the release branch.
+    GitNoPermission {
+        repository_url: String,
+        branch: String,
+        reason: String,
+    },
+    /// The tag format doesn't compile to a valid git reference.
+    InvalidTagFormat { tag_format: String },
+    /// The tag format doesn't contain `${version}` exactly once.
+    TagNoVersion { tag_format: String },
+    /// The tag format of the monorepo packages doesn't contain `${name}`.
+    TagNoName { tag_format: String },
+    /// The initial version isn't a valid semantic version.
+    InvalidInitialVersion { initial_version: String },
+    /// A branch in the `branches` option isn't a valid git branch name.
+    InvalidBranchName { branch: String },
+    /// The `branches` option contains the same branch more than once.
+    DuplicateBranches { duplicates: Vec<String> },
+    /// The `branches` option doesn't contain any release branch.
+    ReleaseBranches,
+    /// The configuration file given on the command line doesn't exist or has no release configuration.
+    NoConfig { path: PathBuf },
+    /// The configuration file couldn't be read or deserialized.
+    InvalidConfig {
+        path: PathBuf,
+        line: Option<usize>,
+        column: Option<usize>,
+        message: String,
+    },
+    /// A `SEMANTIC_RELEASE_*` environment variable overrides an option with an invalid value.
+    InvalidEnvOverride { name: String, message: String },
+    /// A shared configuration in the `extends` option couldn't be resolved.
+    InvalidExtends { extends: String, reason: String },
+    /// Shared configurations in the `extends` option extend each other.
+    ExtendsCycle { chain: Vec<String> },
+    /// A pattern of the `secrets` option isn't a valid regular expression.
+    InvalidSecretPattern { pattern: String, reason: String },
+    /// Packages of a monorepo depend on each other.
+    DependencyCycle { packages: Vec<String> },
+    /// Packages of a monorepo have the same prefix in the release outputs.
+    OutputPrefixCollision {
+        prefix: String,
+        packages: Vec<String>,
+    },
+    /// Reading the history of the repository failed.
+    Git { operation: String, reason: String },
+    /// A plugin failed a release step.
+    PluginFailed {
+        plugin: String,
+        step: String,
+        reason: String,
+    },
+    /// A step that publishes the release failed.
+    PublishFailed { step: String, reason: String },
+    /// The release outputs couldn't be written to a file.
+    WriteOutputs { path: PathBuf, reason: String },
+}
+
+/// Broad classes of errors, each reported with its own process exit code.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorCategory {
+    /// The configuration couldn't be found or loaded.
+    Config,
+    /// The context or the remote repository didn't pass verification.
+    Verification,
+    /// The release couldn't be published.
+    Publish,
+}
+
+impl ErrorCategory {
+    /// The exit code of the process when a run fails with an error of this category.
+    pub fn exit_code(&self) -> u8 {
+        match self {
+            ErrorCategory::Config => 2,
+            ErrorCategory::Verification => 3,
+            ErrorCategory::Publish => 4,
+        }
+    }
+}
+
+impl SemanticReleaseError {
+    /// The error code, e.g. `ENOGITREPO`.
+    pub fn code(&self) -> &'static str {
+        match self {
+            SemanticReleaseError::NoGitRepo { .. } => "ENOGITREPO",
+            SemanticReleaseError::NoRepoUrl => "ENOREPOURL",
+            SemanticReleaseError::InvalidRepoUrl { .. } => "EINVALIDREPOURL",
+            SemanticReleaseError::GitNoPermission { .. } => "EGITNOPERMISSION",
+            SemanticReleaseError::InvalidTagFormat { .. } => "EINVALIDTAGFORMAT",
+            SemanticReleaseError::TagNoVersion { .. } => "ETAGNOVERSION",
+            SemanticReleaseError::TagNoName { .. } => "ETAGNONAME",
+            SemanticReleaseError::InvalidInitialVersion { .. } => "EINVALIDINITIALVERSION",
+            SemanticReleaseError::InvalidBranchName { .. } => "EINVALIDBRANCHNAME",
+            SemanticReleaseError::DuplicateBranches { .. } => "EDUPLICATEBRANCHES",
+            SemanticReleaseError::ReleaseBranches => "ERELEASEBRANCHES",
+            SemanticReleaseError::NoConfig { .. } => "ENOCONFIG",
+            SemanticReleaseError::InvalidConfig { .. } => "EINVALIDCONFIG",
+            SemanticReleaseError::InvalidEnvOverride { .. } => "EINVALIDENVOVERRIDE",
+            SemanticReleaseError::InvalidExtends { .. } => "EINVALIDEXTENDS",
+            SemanticReleaseError::ExtendsCycle { .. } => "EEXTENDSCYCLE",
+            SemanticReleaseError::InvalidSecretPattern { .. } => "EINVALIDSECRETPATTERN",
+            SemanticReleaseError::DependencyCycle { .. } => "EDEPENDENCYCYCLE",
+            SemanticReleaseError::OutputPrefixCollision { .. } => "EOUTPUTPREFIXCOLLISION",
+            SemanticReleaseError::Git { .. } => "EGIT",
+            SemanticReleaseError::PluginFailed { .. } => "EPLUGINFAILED",
+            SemanticReleaseError::PublishFailed { .. } => "EPUBLISH",
+            SemanticReleaseError::WriteOutputs { .. } => "EWRITEOUTPUTS",
+        }
+    }
+
+    /// The category of the error, which determines the exit code.
+    pub fn category(&self) -> ErrorCategory {
+        match self {
+            SemanticReleaseError::NoConfig { .. }
+            | SemanticReleaseError::InvalidConfig { .. }
+            | SemanticReleaseError::InvalidEnvOverride { .. }
+            | SemanticReleaseError::InvalidExtends { .. }
+            | SemanticReleaseError::ExtendsCycle { .. }
+            | SemanticReleaseError::InvalidSecretPattern { .. }
+            | SemanticReleaseError::DependencyCycle { .. }
+            | SemanticReleaseError::OutputPrefixCollision { .. } => ErrorCategory::Config,
+            SemanticReleaseError::PluginFailed { step, .. }
+                if matches!(step.as_str(), "prepare" | "publish" | "success") =>
+            {
+                ErrorCategory::Publish
+            }
+            SemanticReleaseError::PublishFailed { .. }
+            | SemanticReleaseError::WriteOutputs { .. } => ErrorCategory::Publish,
+            _ => ErrorCategory::Verification,
+        }
+    }
+
+    /// A short, human readable description of the error.
+    pub fn message(&self) -> String {
+        match self {
+            SemanticReleaseError::NoGitRepo { .. } => "Not running from a git repository.".into(),
+            SemanticReleaseError::NoRepoUrl => "The `repository_url` option is required.".into(),
+            SemanticReleaseError::InvalidRepoUrl { .. } => {
+                "Invalid `repository_url` option.".into()
+            }
+            SemanticReleaseError::GitNoPermission { .. } => {
+                "Cannot push to the Git repository.".into()
+            }
+            SemanticReleaseError::InvalidTagFormat { .. }
+            | SemanticReleaseError::TagNoVersion { .. } => "Invalid `tag_format` option.".into(),
+            SemanticReleaseError::TagNoName { .. } => {
+                "Invalid `monorepo.tag_format` option.".into()
+            }
+            SemanticReleaseError::InvalidInitialVersion { .. } => {
+                "Invalid `initial_version` option.".into()
+            }
+            SemanticReleaseError::InvalidBranchName { .. } => {
+                "A branch name in the `branches` configuration is invalid.".into()
+            }
+            SemanticReleaseError::DuplicateBranches { .. } => {
+                "The `branches` option contains duplicates.".into()
+            }
+            SemanticReleaseError::ReleaseBranches => {
+                "The release branches are invalid in the `branches` configuration.".into()
+            }
+            SemanticReleaseError::NoConfig { .. } => "No configuration file found.".into(),
+            SemanticReleaseError::InvalidConfig { .. } => "Invalid configuration file.".into(),
+            SemanticReleaseError::InvalidEnvOverride { name, .. } => {
+                format!("Invalid `{}` environment variable.", name)
+            }
+            SemanticReleaseError::InvalidExtends { .. } => "Invalid `extends` option.".into(),
+            SemanticReleaseError::ExtendsCycle { .. } => {
+                "The `extends` option contains a cycle.".into()
+            }
+            SemanticReleaseError::InvalidSecretPattern { .. } => "Invalid `secrets` option.".into(),
+            SemanticReleaseError::DependencyCycle { .. } => {
+                "The packages of the monorepo depend on each other.".into()
+            }
+            SemanticReleaseError::OutputPrefixCollision { .. } => {
+                "Packages of the monorepo have the same output prefix.".into()
+            }
+            SemanticReleaseError::Git { .. } => "A Git operation failed.".into(),
+            SemanticReleaseError::PluginFailed { plugin, step, .. } => {
+                format!("The plugin `{}` failed the `{}` step.", plugin, step)
+            }
+            SemanticReleaseError::PublishFailed { step, .. } => {
+                format!("The `{}` step failed.", step)
+            }
+            SemanticReleaseError::WriteOutputs { .. } => {
+                "Couldn't write the release outputs.".into()
+            }
+        }
+    }
+
+    /// Markdown explanation of the error and of how to fix it.
+    pub fn details(&self) -> String {
+        match self {
+            SemanticReleaseError::NoGitRepo { cwd } => format!(
+                "The `semantic-release` command must be executed from a Git repository.\n\n\
+                 The current working directory is `{}`.\n\n\
+                 Please verify your CI configuration to make sure the `semantic-release` command is executed from the root of the cloned repository.",
+                cwd.display()
+            ),
+            SemanticReleaseError::NoRepoUrl => format!(
+                "The [repository_url option]({docs}/usage/configuration.md#repositoryurl) cannot be determined from the semantic-release configuration nor the [git origin url](https://git-scm.com/book/en/v2/Git-Basics-Working-with-Remotes).\n\n\
+                 Please make sure to add the `repository_url` to the [semantic-release configuration]({docs}/usage/configuration.md).",
+                docs = DOCS_URL
+            ),
+            SemanticReleaseError::InvalidRepoUrl {
+                repository_url,
+                reason,
+            } => format!(
+                "The [repository_url option]({docs}/usage/configuration.md#repositoryurl) must be a URL such as `https://github.com/owner/repo.git` or `ssh://git@github.com/owner/repo.git`, an scp-like address such as `git@github.com:owner/repo.git` or an absolute path ({reason}).\n\n\
+                 Your configuration for the `repository_url` option, or the URL of the `origin` remote, is `{repository_url}`.",
+                docs = DOCS_URL
+            ),
+            SemanticReleaseError::GitNoPermission {
+                repository_url,
+                branch,
+                reason,
+            } => format!(
+                "**semantic-release** cannot push the version tag to the branch `{branch}` on the remote Git repository with URL `{repository_url}` ({reason}).\n\n\
+                 This can be caused by:\n \
+                 - a misconfiguration of the [repository_url]({docs}/usage/configuration.md#repositoryurl) option\n \
+                 - the repository being unavailable\n \
+                 - or missing push permission for the user configured via the [Git credentials on your CI environment]({docs}/usage/ci-configuration.md#authentication)",
+                docs = DOCS_URL
+            ),
+            SemanticReleaseError::InvalidTagFormat { tag_format } => format!(
+                "The [tag_format]({}/usage/configuration.md#tagformat) must compile to a [valid Git reference](https://git-scm.com/docs/git-check-ref-format#_description).\n\n\
+                 Your configuration for the `tag_format` option is `{}`.",
+                DOCS_URL, tag_format
+            ),
+            SemanticReleaseError::TagNoVersion { tag_format } => format!(
+                "The [tag_format]({}/usage/configuration.md#tagformat) option must contain the variable `version` exactly once.\n\n\
+                 Your configuration for the `tag_format` option is `{}`.",
+                DOCS_URL, tag_format
+            ),
+            SemanticReleaseError::TagNoName { tag_format } => format!(
+                "The `tag_format` of the `monorepo` option must contain the variable `name`, so the tags of each package differ.\n\n\
+                 Your configuration for the `monorepo.tag_format` option is `{}`.",
+                tag_format
+            ),
+            SemanticReleaseError::InvalidInitialVersion { initial_version } => format!(
+                "The `initial_version` option, the version of the first release when no tag matches the [tag_format]({}/usage/configuration.md#tagformat), must be a [semantic version](https://semver.org) such as `1.0.0` or `0.1.0`.\n\n\
+                 Your configuration for the `initial_version` option is `{}`.",
+                DOCS_URL, initial_version
+            ),
+            SemanticReleaseError::InvalidBranchName { branch } => format!(
+                "Each branch in the [branches configuration]({}/usage/configuration.md#branches) must be a [valid Git reference](https://git-scm.com/docs/git-check-ref-format#_description).\n\n\
+                 The following branch name is invalid: `{}`.",
+                DOCS_URL, branch
+            ),
+            SemanticReleaseError::DuplicateBranches { duplicates } => format!(
+                "Each branch in the [branches configuration]({}/usage/configuration.md#branches) must be unique.\n\n\
+                 Your configuration contains duplicates for the following branch names: `{}`.",
+                DOCS_URL,
+                duplicates.join("`, `")
+            ),
+            SemanticReleaseError::ReleaseBranches => format!(
+                "A minimum of 1 release branch is required in the [branches configuration]({}/usage/configuration.md#branches).",
+                DOCS_URL
+            ),
+            SemanticReleaseError::NoConfig { path } => format!(
+                "The configuration file `{}` doesn't exist or doesn't contain a release configuration.\n\n\
+                 Please check the path given to `--config`, or remove it to let **semantic-release** look for a `release.toml`, `release.config.toml`, `.releaserc` (JSON, YAML or TOML), `.releaserc.json`, `.releaserc.yaml`, `.releaserc.yml` or `.releaserc.toml` file, a `[package.metadata.release]` table in `Cargo.toml` or a `release` key in `package.json`, in the current directory and its parents up to the repository root.",
+                path.display()
+            ),
+            SemanticReleaseError::InvalidConfig {
+                path,
+                line,
+                column,
+                message,
+            } => {
+                let location = match (line, column) {
+                    (Some(line), Some(column)) => format!("{}:{}:{}", path.display(), line, column),
+                    (Some(line), None) => format!("{}:{}", path.display(), line),
+                    _ => path.display().to_string(),
+                };
+
+                format!(
+                    "The configuration file `{}` couldn't be loaded: {}\n\n\
+                     Please fix it following the [configuration documentation]({}/usage/configuration.md).",
+                    location, message.trim(), DOCS_URL
+                )
+            }
+            SemanticReleaseError::InvalidEnvOverride { name, message } => format!(
+                "The `{}` environment variable overrides a configuration option with an invalid value: {}\n\n\
+                 Please fix its value following the [configuration documentation]({}/usage/configuration.md). Options that aren't strings are parsed as JSON, and array options also accept comma separated values.",
+                name, message.trim(), DOCS_URL
+            ),
+            SemanticReleaseError::InvalidExtends { extends, reason } => format!(
+                "The shared configuration `{}` in the [extends option]({}/usage/configuration.md#extends) couldn't be loaded: {}\n\n\
+                 Each entry must be a path to a configuration file or to a directory containing one, relative to the file that extends it, or a path in a Git repository written `git+<url>#<ref>:<path>`.",
+                extends, DOCS_URL, reason
+            ),
+            SemanticReleaseError::ExtendsCycle { chain } => format!(
+                "The shared configurations in the [extends option]({}/usage/configuration.md#extends) can't extend each other.\n\n\
+                 The following configurations form a cycle: `{}`.",
+                DOCS_URL,
+                chain.join("` -> `")
+            ),
+            SemanticReleaseError::InvalidSecretPattern { pattern, reason } => format!(
+                "The patterns of the `secrets` option must be valid [regular expressions](https://docs.rs/regex/latest/regex/#syntax).\n\n\
+                 The pattern `{}` is invalid: {}",
+                pattern, reason
+            ),
+            SemanticReleaseError::DependencyCycle { packages } => format!(
+                "The packages of a monorepo are released after the packages they depend on, so their dependencies can't form a cycle.\n\n\
+                 The following packages depend on each other: `{}`.\n\n\
+                 Please remove the cycle from their manifests, or list the `dependencies` of the packages in the `monorepo` option.",
+                packages.join("`, `")
+            ),
+            SemanticReleaseError::OutputPrefixCollision { prefix, packages } => format!(
+                "The release outputs of each package of a monorepo are prefixed with its name, where every character that isn't alphanumeric is replaced with `_`, so the names must still differ once replaced.\n\n\
+                 The following packages all have the `{}` prefix: `{}`.\n\n\
+                 Please rename them, or list the `packages` of the `monorepo` option with distinct names.",
+                prefix,
+                packages.join("`, `")
+            ),
+            SemanticReleaseError::Git { operation, reason } => format!(
+                "**semantic-release** couldn't {}: {}.\n\n\
+                 Please make sure the repository is cloned with its full history and tags, e.g. with `fetch-depth: 0` for the `actions/checkout` action on GitHub Actions.",
+                operation, reason
+            ),
+            SemanticReleaseError::PluginFailed {
+                plugin,
+                step,
+                reason,
+            } => format!(
+                "The [plugin]({}/usage/plugins.md) `{}` exited with an error during the `{}` step: {}\n\n\
+                 Please check the output of the plugin above and its configuration.",
+                DOCS_URL, plugin, step, reason
+            ),
+            SemanticReleaseError::PublishFailed { step, reason } => format!(
+                "The release couldn't be completed during the `{}` step: {}\n\n\
+                 The release may have been partially published. Please check the remote repository before running **semantic-release** again.",
+                step, reason
+            ),
+            SemanticReleaseError::WriteOutputs { path, reason } => format!(
+                "The release outputs couldn't be written to `{}`: {}\n\n\
+                 The outputs are written last, so a release of this run was already published. Please check the `--output-file` option and the `GITHUB_OUTPUT` environment variable, then read the outputs of the release from the remote repository.",
+                path.display(),
+                reason
+            ),
+        }
+    }
+}
+
+impl Display for SemanticReleaseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.code(), self.message())
+    }
+}
+
+impl Error for SemanticReleaseError {}
+
+/// A collection of errors reported together, e.g. every problem found while verifying the context.
+#[derive(Debug)]
+pub struct AggregateError {
+    errors: Vec<SemanticReleaseError>,
+}
+
+impl AggregateError {
+    pub fn new(errors: Vec<SemanticReleaseError>) -> Self {
+        AggregateError { errors }
+    }
+
+    pub fn errors(&self) -> &[SemanticReleaseError] {
+        &self.errors
+    }
+
+    pub fn into_errors(self) -> Vec<SemanticReleaseError> {
+        self.errors
+    }
+}
+
+impl From<SemanticReleaseError> for AggregateError {
+    fn from(error: SemanticReleaseError) -> Self {
+        AggregateError::new(vec![error])
+    }
+}
+
+impl Display for AggregateError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let messages = self
+            .errors
+            .iter()
+            .map(|error| error.to_string())
+            .collect::<Vec<String>>();
+
+        write!(f, "{}", messages.join("\n"))
+    }
+}
+
+impl Error for AggregateError {}
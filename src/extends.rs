@@ -0,0 +1,611 @@
+use std::{
+    collections::{hash_map::DefaultHasher, BTreeMap, HashMap},
+    fmt::{self, Display, Formatter},
+    fs,
+    hash::{Hash, Hasher},
+    path::{Component, Path, PathBuf},
+};
+
+use git2::{FetchOptions, ObjectType, Oid, Repository};
+use serde_json::{Map, Value};
+
+use crate::{
+    credentials::{default_credentials, remote_callbacks, CredentialKind},
+    error::SemanticReleaseError,
+    get_config::{parse_config, Config, CONFIG_FILES},
+};
+
+/// Where a configuration was loaded from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConfigSource {
+    /// A file on disk.
+    File(PathBuf),
+    /// A file committed in a Git repository, written `git+<url>#<ref>:<path>`.
+    Git {
+        url: String,
+        reference: String,
+        path: String,
+    },
+}
+
+impl Display for ConfigSource {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigSource::File(path) => write!(f, "{}", path.display()),
+            ConfigSource::Git {
+                url,
+                reference,
+                path,
+            } => write!(f, "git+{}#{}:{}", url, reference, path),
+        }
+    }
+}
+
+/// A configuration merged with the shared configurations it extends.
+#[derive(Debug)]
+pub struct MergedConfig {
+    pub value: Value,
+    /// Every configuration merged, in the order they were applied.
+    pub sources: Vec<ConfigSource>,
+    /// The source each top-level option was taken from.
+    pub provenance: BTreeMap<String, ConfigSource>,
+}
+
+/// Load the configuration file at `file_path` along with the shared configurations
+/// listed in its `extends` option.
+///
+/// Shared configurations are applied in order before the configuration extending them,
+/// and their own `extends` are applied before them. Tables are merged recursively while
+/// any other value, arrays included, replaces the one set before.
+///
+/// Each `extends` entry can be:
+/// - a path to a configuration file, relative to the file that extends it
+/// - a path to a directory, in which the configuration files are looked up
+/// - a path in a Git repository, written `git+<url>#<ref>:<path>`. The ref defaults
+///   to `HEAD` and the path to the root of the repository. Relative entries in a
+///   configuration loaded from a repository are resolved in the same repository.
+///   Repositories are fetched with the `credentials` of the configuration file, or
+///   the default ones.
+///
+/// When `extends` is given, e.g. from the command line, it replaces the `extends`
+/// option of the file. Its relative paths are resolved from the file's directory.
+///
+/// Returns `None` when the file doesn't contain a release configuration.
+pub fn merge_extends(
+    file_path: &Path,
+    extends: Option<&[String]>,
+) -> Result<Option<MergedConfig>, SemanticReleaseError> {
+    let source = ConfigSource::File(
+        file_path
+            .canonicalize()
+            .unwrap_or_else(|_| file_path.to_path_buf()),
+    );
+
+    let mut loader = Loader::default();
+
+    let mut value = match loader.read(&source)? {
+        Some(value) => value,
+        None => return Ok(None),
+    };
+
+    if let (Value::Object(options), Some(extends)) = (&mut value, extends) {
+        options.insert("extends".to_string(), extends.into());
+    }
+
+    loader.credentials = value
+        .get("credentials")
+        .and_then(|credentials| serde_json::from_value(credentials.clone()).ok())
+        .unwrap_or_else(default_credentials);
+
+    let extends = value.get("extends").cloned();
+
+    loader.load(source, value)?;
+
+    Ok(Some(loader.into_merged(extends)))
+}
+
+/// Load the shared configurations `extends` without a configuration file extending
+/// them, resolving their relative paths from `cwd`.
+pub fn merge_shared(cwd: &Path, extends: &[String]) -> Result<MergedConfig, SemanticReleaseError> {
+    let mut loader = Loader {
+        credentials: default_credentials(),
+        ..Loader::default()
+    };
+
+    // The entries are resolved as if they were listed by a config file in `cwd`.
+    let parent = ConfigSource::File(cwd.join(CONFIG_FILES[0]));
+
+    for entry in extends {
+        let (source, value) = loader.resolve(&parent, entry)?;
+
+        loader.load(source, value)?;
+    }
+
+    Ok(loader.into_merged(Some(extends.into())))
+}
+
+/// Merge `value` into `base`: tables are merged recursively, other values replace the base.
+pub fn merge(base: &mut Value, value: Value) {
+    match (base, value) {
+        (Value::Object(base), Value::Object(value)) => {
+            for (key, value) in value {
+                match base.get_mut(&key) {
+                    Some(existing) => merge(existing, value),
+                    None => {
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        (base, value) => *base = value,
+    }
+}
+
+/// Rename the camelCase top-level options to their snake_case name, so the
+/// aliases accepted by [`Config`] don't end up set twice once merged.
+fn normalize_keys(value: Value) -> Value {
+    match value {
+        Value::Object(options) => Value::Object(
+            options
+                .into_iter()
+                .map(|(key, value)| (to_snake_case(&key), value))
+                .collect(),
+        ),
+        value => value,
+    }
+}
+
+fn to_snake_case(key: &str) -> String {
+    let mut snake_case = String::with_capacity(key.len());
+
+    for c in key.chars() {
+        if c.is_ascii_uppercase() {
+            snake_case.push('_');
+            snake_case.push(c.to_ascii_lowercase());
+        } else {
+            snake_case.push(c);
+        }
+    }
+
+    snake_case
+}
+
+/// What a path points to in the tree of a commit.
+enum GitEntry {
+    Blob(String),
+    Tree,
+    Missing,
+}
+
+#[derive(Default)]
+struct Loader {
+    /// Repositories opened or fetched, by URL.
+    repositories: HashMap<String, Repository>,
+    /// Commits the refs resolved to, by URL and ref.
+    commits: HashMap<(String, String), Oid>,
+    /// The configurations being loaded, to detect cycles.
+    stack: Vec<ConfigSource>,
+    /// The loaded configurations, in the order they are merged.
+    layers: Vec<(ConfigSource, Value)>,
+    /// The credential providers remote repositories are fetched with.
+    credentials: Vec<CredentialKind>,
+}
+
+impl Loader {
+    fn load(&mut self, source: ConfigSource, value: Value) -> Result<(), SemanticReleaseError> {
+        let mut value = normalize_keys(value);
+
+        let extends = match &mut value {
+            Value::Object(options) => options.remove("extends"),
+            _ => None,
+        };
+
+        let extends: Vec<String> = extends
+            .and_then(|extends| serde_json::from_value(extends).ok())
+            .unwrap_or_default();
+
+        self.stack.push(source.clone());
+
+        for entry in extends {
+            let (shared_source, shared_value) = self.resolve(&source, &entry)?;
+
+            if self.stack.contains(&shared_source) {
+                let chain = self
+                    .stack
+                    .iter()
+                    .skip_while(|source| **source != shared_source)
+                    .chain([&shared_source])
+                    .map(|source| source.to_string())
+                    .collect();
+
+                return Err(SemanticReleaseError::ExtendsCycle { chain });
+            }
+
+            self.load(shared_source, shared_value)?;
+        }
+
+        self.stack.pop();
+        self.layers.push((source, value));
+
+        Ok(())
+    }
+
+    /// Merge the loaded configurations, keeping `extends` as the `extends` option.
+    fn into_merged(self, extends: Option<Value>) -> MergedConfig {
+        let mut merged = Value::Object(Map::new());
+        let mut provenance = BTreeMap::new();
+
+        for (source, value) in &self.layers {
+            if let Value::Object(options) = value {
+                for key in options.keys() {
+                    provenance.insert(key.clone(), source.clone());
+                }
+            }
+
+            merge(&mut merged, value.clone());
+        }
+
+        // Only the `extends` of the configuration file itself is kept.
+        if let (Value::Object(options), Some(extends)) = (&mut merged, extends) {
+            options.insert("extends".to_string(), extends);
+        }
+
+        MergedConfig {
+            value: merged,
+            sources: self.layers.into_iter().map(|(source, _)| source).collect(),
+            provenance,
+        }
+    }
+
+    /// Find the configuration an `extends` entry of `parent` refers to and read it.
+    fn resolve(
+        &mut self,
+        parent: &ConfigSource,
+        entry: &str,
+    ) -> Result<(ConfigSource, Value), SemanticReleaseError> {
+        let invalid_extends = |reason: String| SemanticReleaseError::InvalidExtends {
+            extends: entry.to_string(),
+            reason,
+        };
+
+        let source = match (entry.strip_prefix("git+"), parent) {
+            (Some(spec), _) => parse_git_spec(spec, parent),
+            (None, ConfigSource::File(path)) => {
+                let path = path.parent().unwrap_or(Path::new("")).join(entry);
+
+                ConfigSource::File(path.canonicalize().unwrap_or(path))
+            }
+            (None, ConfigSource::Git { url, reference, .. }) => ConfigSource::Git {
+                url: url.clone(),
+                reference: reference.clone(),
+                path: join_git_path(parent, entry),
+            },
+        };
+
+        if !self.is_dir(&source).map_err(invalid_extends)? {
+            return match self.read(&source)? {
+                Some(value) => Ok((source, value)),
+                None => Err(invalid_extends(format!(
+                    "`{}` doesn't contain a release configuration",
+                    source
+                ))),
+            };
+        }
+
+        for file_name in CONFIG_FILES {
+            let candidate = match &source {
+                ConfigSource::File(dir) => ConfigSource::File(dir.join(file_name)),
+                ConfigSource::Git {
+                    url,
+                    reference,
+                    path,
+                } => ConfigSource::Git {
+                    url: url.clone(),
+                    reference: reference.clone(),
+                    path: join_path(path, file_name),
+                },
+            };
+
+            if self.exists(&candidate).map_err(invalid_extends)? {
+                if let Some(value) = self.read(&candidate)? {
+                    return Ok((candidate, value));
+                }
+            }
+        }
+
+        Err(invalid_extends(format!(
+            "no configuration file found in `{}`",
+            source
+        )))
+    }
+
+    /// Read the configuration of `source`, validating it as a [`Config`] so
+    /// errors are reported with their location.
+    fn read(&mut self, source: &ConfigSource) -> Result<Option<Value>, SemanticReleaseError> {
+        let label = PathBuf::from(source.to_string());
+
+        let contents = match source {
+            ConfigSource::File(path) => fs::read_to_string(path).map_err(|e| e.to_string()),
+            ConfigSource::Git {
+                url,
+                reference,
+                path,
+            } => match self.git_entry(url, reference, path) {
+                Ok(GitEntry::Blob(contents)) => Ok(contents),
+                Ok(_) => Err("not a file".to_string()),
+                Err(reason) => Err(reason),
+            },
+        }
+        .map_err(|message| SemanticReleaseError::InvalidConfig {
+            path: label.clone(),
+            line: None,
+            column: None,
+            message,
+        })?;
+
+        if parse_config::<Config>(&label, &contents)?.is_none() {
+            return Ok(None);
+        }
+
+        parse_config(&label, &contents)
+    }
+
+    fn is_dir(&mut self, source: &ConfigSource) -> Result<bool, String> {
+        match source {
+            ConfigSource::File(path) => Ok(path.is_dir()),
+            ConfigSource::Git {
+                url,
+                reference,
+                path,
+            } => Ok(matches!(
+                self.git_entry(url, reference, path)?,
+                GitEntry::Tree
+            )),
+        }
+    }
+
+    fn exists(&mut self, source: &ConfigSource) -> Result<bool, String> {
+        match source {
+            ConfigSource::File(path) => Ok(path.is_file()),
+            ConfigSource::Git {
+                url,
+                reference,
+                path,
+            } => Ok(matches!(
+                self.git_entry(url, reference, path)?,
+                GitEntry::Blob(_)
+            )),
+        }
+    }
+
+    fn git_entry(&mut self, url: &str, reference: &str, path: &str) -> Result<GitEntry, String> {
+        let oid = self.commit(url, reference)?;
+        let repo = &self.repositories[url];
+
+        let tree = repo
+            .find_commit(oid)
+            .and_then(|commit| commit.tree())
+            .map_err(|e| e.message().to_string())?;
+
+        if path.is_empty() {
+            return Ok(GitEntry::Tree);
+        }
+
+        let entry = match tree.get_path(Path::new(path)) {
+            Ok(entry) => entry,
+            Err(_) => return Ok(GitEntry::Missing),
+        };
+
+        match entry.kind() {
+            Some(ObjectType::Tree) => Ok(GitEntry::Tree),
+            Some(ObjectType::Blob) => {
+                let blob = repo
+                    .find_blob(entry.id())
+                    .map_err(|e| e.message().to_string())?;
+
+                String::from_utf8(blob.content().to_vec())
+                    .map(GitEntry::Blob)
+                    .map_err(|e| e.to_string())
+            }
+            _ => Ok(GitEntry::Missing),
+        }
+    }
+
+    /// Resolve `reference` in the repository at `url`, fetching it first when the repository is remote.
+    fn commit(&mut self, url: &str, reference: &str) -> Result<Oid, String> {
+        let key = (url.to_string(), reference.to_string());
+
+        if let Some(oid) = self.commits.get(&key) {
+            return Ok(*oid);
+        }
+
+        let is_remote = url.contains("://") || is_scp_like(url);
+
+        if !self.repositories.contains_key(url) {
+            let repo = if is_remote {
+                open_cache(url)
+            } else {
+                Repository::open(url)
+            }
+            .map_err(|e| e.message().to_string())?;
+
+            self.repositories.insert(url.to_string(), repo);
+        }
+
+        let repo = &self.repositories[url];
+
+        let object = if is_remote {
+            let mut fetch_options = FetchOptions::new();
+            fetch_options.remote_callbacks(remote_callbacks(&self.credentials));
+
+            repo.remote_anonymous(url)
+                .and_then(|mut remote| remote.fetch(&[reference], Some(&mut fetch_options), None))
+                .and_then(|_| repo.revparse_single("FETCH_HEAD"))
+        } else {
+            repo.revparse_single(reference)
+        }
+        .map_err(|e| e.message().to_string())?;
+
+        let oid = object
+            .peel_to_commit()
+            .map_err(|e| e.message().to_string())?
+            .id();
+
+        self.commits.insert(key, oid);
+
+        Ok(oid)
+    }
+}
+
+/// Parse a `<url>#<ref>:<path>` spec. Local repository paths are resolved from the
+/// directory of the file that extends it.
+fn parse_git_spec(spec: &str, parent: &ConfigSource) -> ConfigSource {
+    let (url, fragment) = spec.rsplit_once('#').unwrap_or((spec, ""));
+
+    let (reference, path) = fragment.split_once(':').unwrap_or((fragment, ""));
+
+    let url = match parent {
+        ConfigSource::File(file_path) if !url.contains("://") && !is_scp_like(url) => {
+            let path = file_path.parent().unwrap_or(Path::new("")).join(url);
+
+            path.canonicalize()
+                .unwrap_or(path)
+                .to_string_lossy()
+                .into_owned()
+        }
+        _ => url.to_string(),
+    };
+
+    ConfigSource::Git {
+        url,
+        reference: if reference.is_empty() {
+            "HEAD".to_string()
+        } else {
+            reference.to_string()
+        },
+        path: join_path("", path),
+    }
+}
+
+/// Test if `url` is written as `user@host:path`, the scp-like syntax for SSH.
+fn is_scp_like(url: &str) -> bool {
+    match url.split_once(':') {
+        Some((host, _)) => host.contains('@') && !host.contains('/'),
+        None => false,
+    }
+}
+
+/// Resolve `entry` from the directory of a configuration in a Git repository.
+fn join_git_path(parent: &ConfigSource, entry: &str) -> String {
+    match parent {
+        ConfigSource::Git { path, .. } => {
+            let dir = Path::new(path).parent().unwrap_or(Path::new(""));
+
+            join_path(&dir.to_string_lossy(), entry)
+        }
+        ConfigSource::File(_) => join_path("", entry),
+    }
+}
+
+/// Join `path` to `dir` in a tree, resolving `.` and `..` since trees can't be canonicalized.
+fn join_path(dir: &str, path: &str) -> String {
+    let mut components: Vec<String> = vec![];
+
+    for component in Path::new(dir).join(path).components() {
+        match component {
+            Component::Normal(name) => components.push(name.to_string_lossy().into_owned()),
+            Component::ParentDir => {
+                components.pop();
+            }
+            _ => {}
+        }
+    }
+
+    components.join("/")
+}
+
+/// Open the bare repository caching the shared configurations fetched from `url`.
+fn open_cache(url: &str) -> Result<Repository, git2::Error> {
+    let mut hasher = DefaultHasher::new();
+    url.hash(&mut hasher);
+
+    let path = std::env::temp_dir()
+        .join("semantic-release-extends")
+        .join(format!("{:016x}", hasher.finish()));
+
+    Repository::open_bare(&path).or_else(|_| Repository::init_bare(&path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_merge() {
+        let mut base = json!({
+            "branches": ["main", "next"],
+            "secrets": { "names": ["TOKEN"], "detect": true },
+        });
+
+        merge(
+            &mut base,
+            json!({ "branches": ["main"], "secrets": { "detect": false } }),
+        );
+
+        assert_eq!(
+            base,
+            json!({
+                "branches": ["main"],
+                "secrets": { "names": ["TOKEN"], "detect": false },
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_git_spec() {
+        let parent = ConfigSource::Git {
+            url: "https://github.com/owner/configs.git".to_string(),
+            reference: "HEAD".to_string(),
+            path: String::new(),
+        };
+
+        assert_eq!(
+            parse_git_spec(
+                "https://github.com/owner/configs.git#v2:release/base.toml",
+                &parent
+            ),
+            ConfigSource::Git {
+                url: "https://github.com/owner/configs.git".to_string(),
+                reference: "v2".to_string(),
+                path: "release/base.toml".to_string(),
+            }
+        );
+        assert_eq!(
+            parse_git_spec("git@github.com:owner/configs.git", &parent).to_string(),
+            "git+git@github.com:owner/configs.git#HEAD:"
+        );
+        assert_eq!(
+            join_path("release", "../shared/./base.toml"),
+            "shared/base.toml"
+        );
+    }
+
+    #[test]
+    fn test_extends_cycle() {
+        let dir =
+            std::env::temp_dir().join(format!("semantic-release-cycle-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("a.toml"), "extends = [\"b.toml\"]\n").unwrap();
+        fs::write(dir.join("b.toml"), "extends = [\"a.toml\"]\n").unwrap();
+
+        let error = merge_extends(&dir.join("a.toml"), None).unwrap_err();
+
+        fs::remove_dir_all(&dir).unwrap();
+
+        match error {
+            SemanticReleaseError::ExtendsCycle { chain } => assert_eq!(chain.len(), 3),
+            error => panic!("unexpected error {}", error),
+        }
+    }
+}
@@ -1,51 +1,599 @@
-use serde::{Deserialize, Serialize};
 use std::{
+    collections::{BTreeMap, HashMap},
     fs::{self},
-    path::PathBuf,
+    path::{Path, PathBuf},
 };
 
-const CONFIG_NAME: &str = "release";
+use schemars::JsonSchema;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 
-#[derive(Debug, Serialize, Deserialize)]
+use crate::{
+    ci::detect_ci_env,
+    config_env::{apply_env_overrides, expand_env_vars},
+    credentials::{default_credentials, CredentialKind},
+    error::SemanticReleaseError,
+    extends::{merge, merge_extends, merge_shared, ConfigSource},
+    git::GitRepo,
+    hide_sensitive::SecretsConfig,
+    packages::MonorepoConfig,
+};
+
+/// Files that can hold the configuration, in the order they are looked up in each directory.
+pub(crate) const CONFIG_FILES: [&str; 9] = [
+    "release.toml",
+    "release.config.toml",
+    ".releaserc",
+    ".releaserc.json",
+    ".releaserc.yaml",
+    ".releaserc.yml",
+    ".releaserc.toml",
+    "Cargo.toml",
+    "package.json",
+];
+
+/// The release configuration.
+///
+/// Every option is optional and falls back to the semantic-release defaults.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
 pub struct Config {
     pub branches: Vec<String>,
+    /// Whether CI verifications are enabled, which defaults to whether a CI environment
+    /// is detected. Pull request builds don't publish when they are, and runs outside of
+    /// a detected CI environment are dry-runs unless they are disabled explicitly.
     pub ci: bool,
+    pub credentials: Vec<CredentialKind>,
     pub debug: bool,
+    #[serde(alias = "dryRun")]
     pub dry_run: bool,
+    /// Shared configs merged before this one, see [`merge_extends`].
+    pub extends: Vec<String>,
+    /// The version of the first release, when no tag matches the `tag_format`.
+    #[serde(alias = "initialVersion")]
+    pub initial_version: String,
+    /// Independent releases of the packages of a monorepo.
+    pub monorepo: MonorepoConfig,
+    pub plugins: Vec<String>,
+    /// Rules deciding the release type of commits, checked in order before the default
+    /// rules: breaking changes are major, `feat` minor, and `fix` and `perf` patch releases.
+    #[serde(alias = "releaseRules")]
+    pub release_rules: Vec<ReleaseRule>,
+    /// Defaults to the `origin` remote URL of the repository.
+    #[serde(alias = "repositoryUrl")]
     pub repository_url: String,
+    /// Rules deciding which environment variables and values are masked in the output.
+    pub secrets: SecretsConfig,
+    #[serde(alias = "tagFormat")]
     pub tag_format: String,
 }
 
-pub fn find_config(config_file_name: &str) -> Option<(PathBuf, Config)> {
-    let current_dir = std::env::current_dir().expect("Failed to get current directory");
-    let extensions = ["toml"];
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            branches: vec![
+                "+([0-9])?(.{+([0-9]),x}).x".to_string(),
+                "master".to_string(),
+                "main".to_string(),
+                "next".to_string(),
+                "next-major".to_string(),
+            ],
+            ci: detect_ci_env().is_ci,
+            credentials: default_credentials(),
+            debug: false,
+            dry_run: false,
+            extends: vec![],
+            initial_version: "1.0.0".to_string(),
+            monorepo: MonorepoConfig::default(),
+            plugins: vec![],
+            release_rules: vec![],
+            repository_url: String::new(),
+            secrets: SecretsConfig::default(),
+            tag_format: "v${version}".to_string(),
+        }
+    }
+}
+
+/// A rule matching commits to the release they trigger.
+///
+/// Every condition given must match the commit. With none, the rule matches every commit.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct ReleaseRule {
+    /// The conventional commit type, e.g. `docs`.
+    #[serde(rename = "type", default, skip_serializing_if = "Option::is_none")]
+    pub commit_type: Option<String>,
+    /// The conventional commit scope, e.g. `readme`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scope: Option<String>,
+    /// Whether the commit contains a breaking change.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub breaking: Option<bool>,
+    pub release: ReleaseType,
+}
 
-    for dir in current_dir.ancestors() {
-        for ext in extensions {
-            let file_name = format!("{}.{}", config_file_name, ext);
-            let file_path = dir.join(&file_name);
+/// The type of release triggered by a commit.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, JsonSchema,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum ReleaseType {
+    /// The commit doesn't trigger a release.
+    None,
+    Patch,
+    Minor,
+    Major,
+}
 
-            if file_path.is_file() {
-                let contents =
-                    fs::read_to_string(&file_path).expect("Should have been able to read the file");
+/// The formats a config file can be written in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigFormat {
+    Toml,
+    Json,
+    Yaml,
+}
 
-                if ext == "toml" {
-                    let config: Config =
-                        toml::from_str(contents.as_str()).expect("Couldn\'t deserialize config.");
+impl ConfigFormat {
+    /// Get the format from the file extension, or from the contents for
+    /// extension-less files such as `.releaserc`.
+    pub fn detect(file_path: &Path, contents: &str) -> ConfigFormat {
+        match file_path.extension().and_then(|ext| ext.to_str()) {
+            Some("toml") => ConfigFormat::Toml,
+            Some("json") => ConfigFormat::Json,
+            Some("yaml") | Some("yml") => ConfigFormat::Yaml,
+            _ if contents.trim_start().starts_with('{') => ConfigFormat::Json,
+            _ if toml::from_str::<toml::Table>(contents).is_ok() => ConfigFormat::Toml,
+            _ => ConfigFormat::Yaml,
+        }
+    }
+}
 
-                    return Some((file_path, config));
-                }
+#[derive(Deserialize)]
+struct CargoManifest<T> {
+    package: Option<CargoPackage<T>>,
+}
+
+#[derive(Deserialize)]
+struct CargoPackage<T> {
+    metadata: Option<CargoMetadata<T>>,
+}
+
+#[derive(Deserialize)]
+struct CargoMetadata<T> {
+    release: Option<T>,
+}
+
+#[derive(Deserialize)]
+struct PackageJson<T> {
+    release: Option<T>,
+}
+
+/// Deserialize `contents` in the given format, reporting the location of any error in `file_path`.
+pub fn parse<T: DeserializeOwned>(
+    file_path: &Path,
+    contents: &str,
+    format: ConfigFormat,
+) -> Result<T, SemanticReleaseError> {
+    let invalid_config =
+        |message: String, location: Option<(usize, usize)>| SemanticReleaseError::InvalidConfig {
+            path: file_path.to_path_buf(),
+            line: location.map(|(line, _)| line),
+            column: location.map(|(_, column)| column),
+            message,
+        };
+
+    match format {
+        ConfigFormat::Toml => toml::from_str(contents).map_err(|e| {
+            let location = e.span().map(|span| line_column(contents, span.start));
+
+            invalid_config(e.message().to_string(), location)
+        }),
+        ConfigFormat::Json => serde_json::from_str(contents).map_err(|e| {
+            let location = (e.line() > 0).then(|| (e.line(), e.column()));
+
+            invalid_config(e.to_string(), location)
+        }),
+        ConfigFormat::Yaml => serde_yaml::from_str(contents).map_err(|e| {
+            let location = e
+                .location()
+                .map(|location| (location.line(), location.column()));
+
+            invalid_config(e.to_string(), location)
+        }),
+    }
+}
+
+/// Get the 1-based line and column of a byte offset.
+pub(crate) fn line_column(contents: &str, offset: usize) -> (usize, usize) {
+    let before = &contents[..offset.min(contents.len())];
+    let line = before.matches('\n').count() + 1;
+    let column = before.len() - before.rfind('\n').map_or(0, |i| i + 1) + 1;
+
+    (line, column)
+}
+
+/// Read and deserialize the config file at `file_path`.
+///
+/// `Cargo.toml` and `package.json` are read from their `[package.metadata.release]`
+/// table and `release` key respectively, and yield `None` when they don't have one.
+pub fn read_config<T: DeserializeOwned>(
+    file_path: &Path,
+) -> Result<Option<T>, SemanticReleaseError> {
+    let contents =
+        fs::read_to_string(file_path).map_err(|e| SemanticReleaseError::InvalidConfig {
+            path: file_path.to_path_buf(),
+            line: None,
+            column: None,
+            message: e.to_string(),
+        })?;
+
+    parse_config(file_path, &contents)
+}
+
+/// Deserialize the `contents` of the config file at `file_path`, as [`read_config`] does.
+pub(crate) fn parse_config<T: DeserializeOwned>(
+    file_path: &Path,
+    contents: &str,
+) -> Result<Option<T>, SemanticReleaseError> {
+    let file_name = file_path.file_name().and_then(|name| name.to_str());
+
+    if file_name == Some("Cargo.toml") {
+        let manifest: CargoManifest<T> = parse(file_path, contents, ConfigFormat::Toml)?;
+
+        return Ok(manifest
+            .package
+            .and_then(|package| package.metadata)
+            .and_then(|metadata| metadata.release));
+    }
+
+    if file_name == Some("package.json") {
+        let manifest: PackageJson<T> = parse(file_path, contents, ConfigFormat::Json)?;
+
+        return Ok(manifest.release);
+    }
+
+    let format = ConfigFormat::detect(file_path, contents);
+
+    parse(file_path, contents, format).map(Some)
+}
+
+/// Look for a config file in `cwd` and its parents, up to the root of the
+/// repository `cwd` belongs to, and return its path along with the config.
+///
+/// When `cwd` isn't in a repository, every ancestor is searched.
+///
+/// # Example
+///
+/// ```no_run
+/// # use semantic_release::get_config::find_config;
+/// if let Some((file_path, _config)) = find_config(&std::env::current_dir().unwrap()).unwrap() {
+///     println!("Loaded config from {}", file_path.display());
+/// }
+/// ```
+pub fn find_config(cwd: &Path) -> Result<Option<(PathBuf, Config)>, SemanticReleaseError> {
+    let file_path = match find_config_file(cwd)? {
+        Some(file_path) => file_path,
+        None => return Ok(None),
+    };
+
+    let config = read_config(&file_path)?.expect("Config file should have a release config");
+
+    Ok(Some((file_path, config)))
+}
+
+/// Look for a file with a release config as [`find_config`] does and return its path.
+pub fn find_config_file(cwd: &Path) -> Result<Option<PathBuf>, SemanticReleaseError> {
+    let repository_root = git2::Repository::discover(cwd)
+        .ok()
+        .and_then(|repo| repo.workdir().map(Path::to_path_buf));
+
+    for dir in cwd.ancestors() {
+        for file_name in CONFIG_FILES {
+            let file_path = dir.join(file_name);
+
+            if file_path.is_file() && read_config::<serde_json::Value>(&file_path)?.is_some() {
+                return Ok(Some(file_path));
             }
         }
+
+        let is_repository_root = repository_root
+            .as_deref()
+            .is_some_and(|root| same_dir(root, dir));
+
+        if is_repository_root {
+            break;
+        }
     }
 
-    None
+    Ok(None)
 }
 
-pub fn get_config() -> Option<Config> {
-    if let Some((_, config)) = find_config(CONFIG_NAME) {
-        Some(config)
-    } else {
-        None
+fn same_dir(a: &Path, b: &Path) -> bool {
+    match (a.canonicalize(), b.canonicalize()) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => a == b,
+    }
+}
+
+/// A config along with where it was loaded from.
+#[derive(Debug)]
+pub struct LoadedConfig {
+    pub config: Config,
+    /// The config file, or `None` when the defaults are used.
+    pub file_path: Option<PathBuf>,
+    /// The config file and the shared configs it extends, in the order they were merged.
+    pub sources: Vec<ConfigSource>,
+    /// Where each configured option was taken from: a config source or an environment variable.
+    pub provenance: BTreeMap<String, String>,
+    /// The undefined environment variables used without a default, which expanded to an empty string.
+    pub undefined_vars: Vec<String>,
+}
+
+/// Load the config from `config_path`, or look for it from `cwd` when not given,
+/// merged with the shared configs listed in its `extends` option.
+///
+/// `extends`, e.g. from the command line, replaces the `extends` option of the file,
+/// with its relative paths resolved from `cwd`. It is loaded even without a config file.
+///
+/// `${VAR}` and `${VAR:-default}` in string options are expanded from the environment,
+/// then the `SEMANTIC_RELEASE_*` environment variables override the options from the
+/// files, see [`apply_env_overrides`]. An override with an invalid value is reported
+/// against its variable.
+///
+/// Without a config file the defaults are used. When `repository_url` isn't
+/// configured, the `origin` remote URL of the repository is used.
+pub fn load_config(
+    cwd: &Path,
+    config_path: Option<&Path>,
+    extends: Option<&[String]>,
+) -> Result<LoadedConfig, SemanticReleaseError> {
+    let file_path = match config_path {
+        Some(path) => Some(path.to_path_buf()),
+        None => find_config_file(cwd)?,
+    };
+
+    let extends: Option<Vec<String>> = extends.map(|extends| {
+        extends
+            .iter()
+            .map(|entry| {
+                if entry.starts_with("git+") {
+                    entry.clone()
+                } else {
+                    cwd.join(entry).to_string_lossy().into_owned()
+                }
+            })
+            .collect()
+    });
+
+    let merged = match (&file_path, &extends) {
+        (Some(path), _) => match path
+            .is_file()
+            .then(|| merge_extends(path, extends.as_deref()))
+            .transpose()?
+        {
+            Some(Some(merged)) => Some(merged),
+            _ => return Err(SemanticReleaseError::NoConfig { path: path.clone() }),
+        },
+        (None, Some(extends)) => Some(merge_shared(cwd, extends)?),
+        (None, None) => None,
+    };
+
+    let env: HashMap<String, String> = std::env::vars().collect();
+
+    let mut value = serde_json::to_value(Config::default()).expect("Config should serialize");
+    let mut sources = vec![];
+    let mut provenance = BTreeMap::new();
+    let mut undefined_vars = vec![];
+
+    if let Some(mut merged) = merged {
+        undefined_vars = expand_env_vars(&mut merged.value, &env);
+        merge(&mut value, merged.value);
+
+        sources = merged.sources;
+        provenance = merged
+            .provenance
+            .into_iter()
+            .map(|(option, source)| (option, source.to_string()))
+            .collect();
+    }
+
+    let overrides = apply_env_overrides(&mut value, &env);
+
+    let config = serde_json::from_value(value.clone()).map_err(|e| {
+        let path = match (&file_path, sources.last()) {
+            (Some(path), _) => path.clone(),
+            (None, Some(source)) => PathBuf::from(source.to_string()),
+            (None, None) => cwd.to_path_buf(),
+        };
+
+        invalid_config(e, &value, &overrides, path)
+    })?;
+
+    for (option, name) in overrides {
+        provenance.insert(option, format!("environment variable {}", name));
+    }
+
+    let mut loaded = LoadedConfig {
+        config,
+        file_path,
+        sources,
+        provenance,
+        undefined_vars,
+    };
+
+    if loaded.config.repository_url.is_empty() {
+        if let Some(url) = GitRepo::open(cwd)
+            .ok()
+            .and_then(|repo| repo.get_repository_url().ok())
+        {
+            loaded.config.repository_url = url;
+        }
+    }
+
+    Ok(loaded)
+}
+
+/// Report a config that failed to deserialize against the first override that is
+/// invalid on its own, or against the config file at `path` otherwise.
+fn invalid_config(
+    error: serde_json::Error,
+    value: &serde_json::Value,
+    overrides: &[(String, String)],
+    path: PathBuf,
+) -> SemanticReleaseError {
+    for (option, name) in overrides {
+        let mut defaults =
+            serde_json::to_value(Config::default()).expect("Config should serialize");
+
+        defaults[option] = value[option].clone();
+
+        if let Err(e) = serde_json::from_value::<Config>(defaults) {
+            return SemanticReleaseError::InvalidEnvOverride {
+                name: name.clone(),
+                message: e.to_string(),
+            };
+        }
+    }
+
+    SemanticReleaseError::InvalidConfig {
+        path,
+        line: None,
+        column: None,
+        message: error.to_string(),
+    }
+}
+
+/// Load the config of the current directory.
+pub fn get_config() -> Result<Config, SemanticReleaseError> {
+    let current_dir = std::env::current_dir().expect("Failed to get current directory");
+
+    load_config(&current_dir, None, None).map(|loaded| loaded.config)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_format() {
+        let releaserc = Path::new(".releaserc");
+
+        assert_eq!(
+            ConfigFormat::detect(releaserc, "{ \"branches\": [\"main\"] }"),
+            ConfigFormat::Json
+        );
+        assert_eq!(
+            ConfigFormat::detect(releaserc, "branches = [\"main\"]"),
+            ConfigFormat::Toml
+        );
+        assert_eq!(
+            ConfigFormat::detect(releaserc, "branches:\n  - main\n"),
+            ConfigFormat::Yaml
+        );
+        assert_eq!(
+            ConfigFormat::detect(Path::new(".releaserc.yml"), "{}"),
+            ConfigFormat::Yaml
+        );
+    }
+
+    #[test]
+    fn test_partial_config() {
+        let config: Config = parse(
+            Path::new("release.toml"),
+            "branches = [\"main\"]",
+            ConfigFormat::Toml,
+        )
+        .unwrap();
+
+        assert_eq!(config.branches, vec!["main"]);
+        assert_eq!(config.tag_format, "v${version}");
+        assert_eq!(config.ci, detect_ci_env().is_ci);
+        assert!(!config.dry_run);
+    }
+
+    #[test]
+    fn test_invalid_override() {
+        let mut value = serde_json::to_value(Config::default()).unwrap();
+        value["ci"] = serde_json::json!("sometimes");
+
+        let overrides = vec![("ci".to_string(), "SEMANTIC_RELEASE_CI".to_string())];
+        let error = serde_json::from_value::<Config>(value.clone()).unwrap_err();
+
+        match invalid_config(error, &value, &overrides, PathBuf::from("release.toml")) {
+            SemanticReleaseError::InvalidEnvOverride { name, .. } => {
+                assert_eq!(name, "SEMANTIC_RELEASE_CI")
+            }
+            error => panic!("unexpected error {}", error),
+        }
+
+        let error = serde_json::from_value::<Config>(value.clone()).unwrap_err();
+
+        match invalid_config(error, &value, &[], PathBuf::from("release.toml")) {
+            SemanticReleaseError::InvalidConfig { path, .. } => {
+                assert_eq!(path, PathBuf::from("release.toml"))
+            }
+            error => panic!("unexpected error {}", error),
+        }
+    }
+
+    #[test]
+    fn test_extends_override() {
+        let dir = std::env::temp_dir().join(format!(
+            "semantic-release-cli-extends-{}",
+            std::process::id()
+        ));
+        fs::create_dir_all(dir.join("shared")).unwrap();
+        fs::write(
+            dir.join("release.toml"),
+            "tag_format = \"release-${version}\"\n",
+        )
+        .unwrap();
+        fs::write(
+            dir.join("shared/release.toml"),
+            "branches = [\"stable\"]\ntag_format = \"shared-${version}\"\n",
+        )
+        .unwrap();
+
+        let extends = ["shared".to_string()];
+
+        let with_file = load_config(&dir, None, Some(&extends)).unwrap().config;
+
+        fs::remove_file(dir.join("release.toml")).unwrap();
+
+        let without_file = load_config(&dir, None, Some(&extends)).unwrap().config;
+
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(with_file.branches, ["stable"]);
+        assert_eq!(with_file.tag_format, "release-${version}");
+        assert_eq!(without_file.branches, ["stable"]);
+        assert_eq!(without_file.tag_format, "shared-${version}");
+    }
+
+    #[test]
+    fn test_parse_error_location() {
+        let error = parse::<Config>(
+            Path::new("release.toml"),
+            "branches = [\"main\"]\ndry_run = \"yes\"\n",
+            ConfigFormat::Toml,
+        )
+        .unwrap_err();
+
+        match error {
+            SemanticReleaseError::InvalidConfig { line, column, .. } => {
+                assert_eq!((line, column), (Some(2), Some(11)));
+            }
+            error => panic!("unexpected error {}", error),
+        }
+
+        let error = parse::<Config>(
+            Path::new(".releaserc.json"),
+            "{\n  \"branches\": \"main\"\n}",
+            ConfigFormat::Json,
+        )
+        .unwrap_err();
+
+        match error {
+            SemanticReleaseError::InvalidConfig { line, .. } => assert_eq!(line, Some(2)),
+            error => panic!("unexpected error {}", error),
+        }
     }
 }
@@ -1,7 +1,11 @@
+// Port of `hosted-git-info` in progress. Its items aren't used yet, hence the
+// `dead_code` allowances to remove once it's wired up.
+
 use std::collections::HashMap;
 
 use url::Url;
 
+#[allow(dead_code)]
 fn is_github_shorthand(arg: &str) -> bool {
     let first_hash = arg.find('#');
     let first_slash = arg.find('/');
@@ -37,27 +41,33 @@ fn is_github_shorthand(arg: &str) -> bool {
         && second_slash_only_after_hash
 }
 
-fn parse_url(giturl: &str, protocols: &HashMap<String, Protocol>) -> Option<Url> {
+#[allow(dead_code)]
+fn parse_url(giturl: &str) -> Option<Url> {
     Url::from_file_path(giturl).ok()
 }
 
+#[allow(dead_code)]
 #[derive(Debug, Clone)]
 struct Protocol {
     auth: bool,
     name: Option<String>,
 }
 
+/// The host name, the auth, the default representation and the options of a git URL.
+type HostedInfo = (
+    String,
+    Option<String>,
+    Option<String>,
+    HashMap<String, String>,
+);
+
+#[allow(dead_code)]
 fn main_function(
     giturl: &str,
     opts: HashMap<String, String>,
     git_hosts: &GitHosts,
     protocols: &HashMap<String, Protocol>,
-) -> Option<(
-    String,
-    Option<String>,
-    Option<String>,
-    HashMap<String, String>,
-)> {
+) -> Option<HostedInfo> {
     if giturl.is_empty() {
         return None;
     }
@@ -68,7 +78,7 @@ fn main_function(
         giturl.to_string()
     };
 
-    let parsed = parse_url(&corrected_url, protocols)?;
+    let parsed = parse_url(&corrected_url)?;
     let parsed_protocol = parsed.scheme();
     let parsed_host = parsed.host_str().unwrap_or("").to_string();
     let git_host_shortcut = git_hosts.by_shortcut.get(parsed_protocol);
@@ -119,17 +129,20 @@ fn main_function(
     Some((git_host_name.clone(), auth, default_representation, opts))
 }
 
+#[allow(dead_code)]
 struct GitHosts {
     by_shortcut: HashMap<String, String>,
     by_domain: HashMap<String, String>,
     data: HashMap<String, GitHostInfo>,
 }
 
+#[allow(dead_code)]
 struct GitHostInfo {
     protocols: Vec<String>,
     extract: fn(Url) -> Option<UrlSegments>,
 }
 
+#[allow(dead_code)]
 struct UrlSegments {
     user: Option<String>,
     project: Option<String>,
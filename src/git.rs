@@ -1,6 +1,779 @@
-use std::str::from_utf8;
+use std::{
+    cell::RefCell,
+    error::Error,
+    fmt::{self, Debug, Display, Formatter},
+    path::{Path, PathBuf},
+    str::from_utf8,
+};
 
-use git2::{Cred, Direction, ObjectType, Oid, PushOptions, RemoteCallbacks, Repository};
+use git2::{
+    DiffOptions, Direction, ErrorClass, ErrorCode, ObjectType, Oid, Pathspec, PathspecFlags,
+    PushOptions, Repository, Status, Time,
+};
+
+use serde::Serialize;
+
+use crate::{
+    branches,
+    credentials::{remote_callbacks, CredentialKind},
+    generate_notes::civil_from_days,
+};
+
+/// Parts of the messages of SSH and HTTP errors caused by rejected credentials.
+const AUTH_ERROR_MESSAGES: [&str; 6] = [
+    "authenticat",
+    "publickey",
+    "permission denied",
+    "401",
+    "403",
+    "no valid credentials",
+];
+
+/// Reason why a network operation on the remote repository failed.
+#[derive(Debug)]
+pub enum AuthError {
+    /// None of the credential providers were accepted by the remote.
+    Auth(git2::Error),
+    /// The remote couldn't be reached.
+    Network(git2::Error),
+    /// The credentials were accepted but the remote refused to update a reference,
+    /// e.g. because of protection rules. Only a push reports it, see [`GitRepo::push_tag`]
+    /// and [`GitRepo::push_branch`].
+    Rejected(String),
+}
+
+impl AuthError {
+    /// Tell rejected credentials from network failures, by the code of `error`, or its
+    /// message for the SSH and HTTP errors libgit2 reports without a specific code.
+    fn from_git_error(error: git2::Error) -> Self {
+        let message = error.message().to_lowercase();
+
+        let is_auth = error.code() == ErrorCode::Auth
+            || matches!(
+                error.class(),
+                ErrorClass::Ssh | ErrorClass::Http | ErrorClass::Callback
+            ) && AUTH_ERROR_MESSAGES
+                .iter()
+                .any(|part| message.contains(part));
+
+        if is_auth {
+            AuthError::Auth(error)
+        } else {
+            AuthError::Network(error)
+        }
+    }
+}
+
+/// What a push access check could establish, see [`GitRepo::verify_auth`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PushAccess {
+    /// The remote accepted a push connection with the credentials, and has the branch.
+    Verified,
+    /// The remote accepted a push connection, but the access to the branch couldn't
+    /// be verified, for the given reason.
+    Unverified(String),
+}
+
+impl Display for AuthError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            AuthError::Auth(error) => write!(f, "authentication failed: {}", error.message()),
+            AuthError::Network(error) => {
+                write!(f, "couldn\'t reach the remote: {}", error.message())
+            }
+            AuthError::Rejected(reason) => write!(f, "the push was rejected: {}", reason),
+        }
+    }
+}
+
+impl Error for AuthError {}
+
+/// A commit of the repository.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct Commit {
+    pub hash: String,
+    /// The abbreviated hash, its first 7 characters.
+    pub short_hash: String,
+    pub author: Signature,
+    pub committer: Signature,
+    pub message: String,
+    /// The hashes of the parent commits, the first parent first.
+    pub parents: Vec<String>,
+    /// The files changed compared to the first parent, or added by a root commit.
+    /// Only retrieved on request, see [`CommitQuery::files`].
+    pub files: Vec<PathBuf>,
+}
+
+/// Who authored or committed a commit, and when.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct Signature {
+    pub name: String,
+    pub email: String,
+    /// The date in the RFC 3339 format, with the time zone of the signature,
+    /// e.g. `2024-01-01T12:30:00+02:00`.
+    pub date: String,
+}
+
+impl Signature {
+    fn from_git(signature: &git2::Signature) -> Self {
+        Signature {
+            name: signature.name().unwrap_or_default().to_string(),
+            email: signature.email().unwrap_or_default().to_string(),
+            date: format_time(signature.when()),
+        }
+    }
+}
+
+/// Which commits [`GitRepo::query_commits`] retrieves.
+///
+/// # Example
+///
+/// ```no_run
+/// # use semantic_release::git::{CommitQuery, GitRepo};
+/// let repo = GitRepo::open(std::env::current_dir().unwrap()).unwrap();
+///
+/// let commits = repo
+///     .query_commits(&CommitQuery {
+///         since_tag: Some("core@1.0.0".to_string()),
+///         paths: vec!["crates/core".into()],
+///         exclude_merges: true,
+///         ..CommitQuery::default()
+///     })
+///     .unwrap();
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CommitQuery {
+    /// The commit the history is walked from, HEAD when `None`.
+    pub to: Option<Oid>,
+    /// Stop at the commit of this tag, leaving it and its ancestors out. Without it, the
+    /// history is walked up to the root commits.
+    pub since_tag: Option<String>,
+    /// Only retrieve the commits changing files matching one of these pathspecs,
+    /// e.g. `crates/core` or `*.rs`. Every commit matches when empty.
+    pub paths: Vec<PathBuf>,
+    /// Ignore the changes to files matching one of these pathspecs, e.g. the directories
+    /// of the packages nested in the one of `paths`.
+    pub exclude_paths: Vec<PathBuf>,
+    /// Leave out the commits with more than one parent.
+    pub exclude_merges: bool,
+    /// Retrieve the files each commit changes, which takes a diff per commit.
+    pub files: bool,
+}
+
+/// A git repository opened once and shared by all the git operations of a run.
+pub struct GitRepo {
+    repo: Repository,
+}
+
+impl Debug for GitRepo {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("GitRepo")
+            .field("path", &self.repo.path())
+            .finish()
+    }
+}
+
+impl GitRepo {
+    /// Open the repository containing `path`, searching the parent directories
+    /// if `path` itself isn't the root of a repository.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use semantic_release::git::GitRepo;
+    /// let repo = GitRepo::open(std::env::current_dir().unwrap()).unwrap();
+    /// ```
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, git2::Error> {
+        Ok(GitRepo {
+            repo: Repository::discover(path)?,
+        })
+    }
+
+    /// Open the repository the same way `git` does, honoring `GIT_DIR` and
+    /// searching from the current directory.
+    pub fn open_from_env() -> Result<Self, git2::Error> {
+        Ok(GitRepo {
+            repo: Repository::open_from_env()?,
+        })
+    }
+
+    /// The underlying `git2` repository.
+    pub fn repository(&self) -> &Repository {
+        &self.repo
+    }
+
+    /// Get the commit **SHA** for a given tag.
+    pub fn get_tag_head(&self, tag_name: &str) -> Result<Oid, git2::Error> {
+        Ok(self.repo.revparse_single(tag_name)?.id())
+    }
+
+    /// Get all the tags for a given remote branch.
+    pub fn get_tags(&self, branch: &str) -> Result<Vec<String>, git2::Error> {
+        let mut tags = Vec::new();
+
+        let branch_commit = self
+            .repo
+            .find_branch(branch, git2::BranchType::Remote)?
+            .get()
+            .peel_to_commit()?;
+
+        let mut error = None;
+
+        self.repo.tag_foreach(|tag_id, name| {
+            let Ok(object) = self.repo.find_object(tag_id, Some(ObjectType::Any)) else {
+                return true;
+            };
+
+            let Ok(name) = from_utf8(name) else {
+                return true;
+            };
+
+            if let Some(lightweight_tag) = object.as_commit() {
+                match self
+                    .repo
+                    .graph_descendant_of(branch_commit.id(), lightweight_tag.id())
+                {
+                    Ok(true) => tags.push(name.to_string()),
+                    Ok(false) => {}
+                    Err(e) => {
+                        error = Some(e);
+                        return false;
+                    }
+                }
+            } else if let Some(annotated_tag) = object.as_tag() {
+                if annotated_tag.target_id() == branch_commit.id() {
+                    tags.push(name.to_string());
+                }
+            }
+
+            true
+        })?;
+
+        match error {
+            Some(e) => Err(e),
+            None => Ok(tags),
+        }
+    }
+
+    /// Retrieve the messages of the commits reachable from `to` but not from `from`,
+    /// or up to the root commits without `from`.
+    pub fn get_commits(&self, from: Option<&str>, to: &str) -> Result<Vec<String>, git2::Error> {
+        let mut revwalk = self.repo.revwalk()?;
+
+        revwalk.push(Oid::from_str(to)?)?;
+
+        if let Some(from) = from {
+            revwalk.hide(Oid::from_str(from)?)?;
+        }
+
+        let mut commits = Vec::new();
+
+        for oid in revwalk {
+            let commit = self.repo.find_commit(oid?)?;
+
+            if let Some(message) = commit.message() {
+                commits.push(message.to_string());
+            }
+        }
+
+        Ok(commits)
+    }
+
+    /// Retrieve the commits reachable from `to` but not from `from`, or up to the root
+    /// commits without `from`, newest first, without their files.
+    pub fn get_commit_log(&self, from: Option<Oid>, to: Oid) -> Result<Vec<Commit>, git2::Error> {
+        let mut revwalk = self.repo.revwalk()?;
+
+        revwalk.push(to)?;
+
+        if let Some(from) = from {
+            revwalk.hide(from)?;
+        }
+
+        revwalk.map(|oid| self.read_commit(oid?, false)).collect()
+    }
+
+    /// Retrieve the commits matching `query`, newest first.
+    pub fn query_commits(&self, query: &CommitQuery) -> Result<Vec<Commit>, git2::Error> {
+        let mut revwalk = self.repo.revwalk()?;
+
+        match query.to {
+            Some(to) => revwalk.push(to)?,
+            None => revwalk.push_head()?,
+        }
+
+        if let Some(tag) = &query.since_tag {
+            let tag_head = self
+                .repo
+                .revparse_single(&format!("refs/tags/{}", tag))?
+                .peel_to_commit()?
+                .id();
+
+            revwalk.hide(tag_head)?;
+        }
+
+        let paths: Vec<&Path> = query.paths.iter().map(PathBuf::as_path).collect();
+        let exclude_pathspec = Pathspec::new(&query.exclude_paths)?;
+
+        let mut commits = vec![];
+
+        for oid in revwalk {
+            let oid = oid?;
+            let commit = self.read_commit(oid, query.files)?;
+
+            if query.exclude_merges && commit.parents.len() > 1 {
+                continue;
+            }
+
+            let changes_paths = match (paths.is_empty(), query.exclude_paths.is_empty()) {
+                (true, true) => true,
+                (false, true) => self.commit_changes_paths(oid, &paths)?,
+                (_, false) => self
+                    .commit_changed_files(oid, &paths)?
+                    .iter()
+                    .any(|file| !exclude_pathspec.matches_path(file, PathspecFlags::DEFAULT)),
+            };
+
+            if changes_paths {
+                commits.push(commit);
+            }
+        }
+
+        Ok(commits)
+    }
+
+    /// Test if the commit `oid` changes a file under one of `paths`, compared to its first
+    /// parent. Root commits are compared to an empty tree.
+    pub fn commit_changes_paths(&self, oid: Oid, paths: &[&Path]) -> Result<bool, git2::Error> {
+        Ok(!self.commit_changed_files(oid, paths)?.is_empty())
+    }
+
+    /// Get the files the commit `oid` changes under one of `paths`, or anywhere without
+    /// `paths`, compared to its first parent. Root commits are compared to an empty tree.
+    pub fn commit_changed_files(
+        &self,
+        oid: Oid,
+        paths: &[&Path],
+    ) -> Result<Vec<PathBuf>, git2::Error> {
+        let commit = self.repo.find_commit(oid)?;
+        let tree = commit.tree()?;
+        let parent_tree = match commit.parent(0) {
+            Ok(parent) => Some(parent.tree()?),
+            Err(_) => None,
+        };
+
+        let mut options = DiffOptions::new();
+
+        for path in paths {
+            options.pathspec(path);
+        }
+
+        let diff =
+            self.repo
+                .diff_tree_to_tree(parent_tree.as_ref(), Some(&tree), Some(&mut options))?;
+
+        let files = diff
+            .deltas()
+            .filter_map(|delta| delta.new_file().path().or(delta.old_file().path()))
+            .map(Path::to_path_buf)
+            .collect();
+
+        Ok(files)
+    }
+
+    /// Get the commit `oid` with the files it changes.
+    pub fn get_commit(&self, oid: Oid) -> Result<Commit, git2::Error> {
+        self.read_commit(oid, true)
+    }
+
+    /// Get the commit `oid`, with the files it changes only when `files` is set.
+    fn read_commit(&self, oid: Oid, files: bool) -> Result<Commit, git2::Error> {
+        let commit = self.repo.find_commit(oid)?;
+
+        let files = if files {
+            self.commit_changed_files(oid, &[])?
+        } else {
+            vec![]
+        };
+
+        let hash = commit.id().to_string();
+        let author = Signature::from_git(&commit.author());
+        let committer = Signature::from_git(&commit.committer());
+
+        Ok(Commit {
+            short_hash: hash[..7].to_string(),
+            hash,
+            author,
+            committer,
+            message: commit.message().unwrap_or_default().to_string(),
+            parents: commit.parent_ids().map(|oid| oid.to_string()).collect(),
+            files,
+        })
+    }
+
+    /// Get the tags pointing to `head` or one of its ancestors, by name with the commit they point to.
+    pub fn get_reachable_tags(&self, head: Oid) -> Result<Vec<(String, Oid)>, git2::Error> {
+        let mut tags = Vec::new();
+
+        for name in self.repo.tag_names(None)?.iter().flatten() {
+            // Tags of trees or blobs can't be releases.
+            let Ok(target) = self
+                .repo
+                .revparse_single(&format!("refs/tags/{}", name))?
+                .peel_to_commit()
+            else {
+                continue;
+            };
+            let target = target.id();
+
+            if target == head || self.repo.graph_descendant_of(head, target)? {
+                tags.push((name.to_string(), target));
+            }
+        }
+
+        Ok(tags)
+    }
+
+    /// Test if `branch` exists locally or on a remote.
+    pub fn branch_exists(&self, branch: &str) -> bool {
+        let suffix = format!("/{}", branch);
+
+        self.is_ref_exists(&format!("refs/heads/{}", branch))
+            || self
+                .get_branches()
+                .is_ok_and(|branches| branches.iter().any(|name| name.ends_with(&suffix)))
+    }
+
+    /// Create a lightweight tag named `tag_name` pointing to `oid`.
+    pub fn create_tag(&self, tag_name: &str, oid: Oid) -> Result<(), git2::Error> {
+        let object = self.repo.find_object(oid, None)?;
+
+        self.repo.tag_lightweight(tag_name, &object, false)?;
+
+        Ok(())
+    }
+
+    /// Delete the local tag `tag_name`.
+    pub fn delete_tag(&self, tag_name: &str) -> Result<(), git2::Error> {
+        self.repo.tag_delete(tag_name)
+    }
+
+    /// Commit the changes of the files at `paths` on top of HEAD, with the committer of the
+    /// `GIT_COMMITTER_NAME` and `GIT_COMMITTER_EMAIL` environment variables, or else of the
+    /// repository config. Returns `None` when none of the files changed.
+    pub fn commit_files(
+        &self,
+        paths: &[PathBuf],
+        message: &str,
+    ) -> Result<Option<Oid>, git2::Error> {
+        let workdir = self
+            .repo
+            .workdir()
+            .ok_or_else(|| git2::Error::from_str("the repository has no working directory"))?
+            .canonicalize()
+            .map_err(|e| git2::Error::from_str(&e.to_string()))?;
+
+        let mut index = self.repo.index()?;
+        let mut changed = false;
+
+        for path in paths {
+            let path = path
+                .canonicalize()
+                .map_err(|e| git2::Error::from_str(&e.to_string()))?;
+            let path = path.strip_prefix(&workdir).map_err(|_| {
+                git2::Error::from_str(&format!("{} is outside the repository", path.display()))
+            })?;
+
+            if self.repo.status_file(path)? != Status::CURRENT {
+                index.add_path(path)?;
+                changed = true;
+            }
+        }
+
+        if !changed {
+            return Ok(None);
+        }
+
+        index.write()?;
+
+        let tree = self.repo.find_tree(index.write_tree()?)?;
+        let parent = self.repo.head()?.peel_to_commit()?;
+
+        let signature = match (
+            std::env::var("GIT_COMMITTER_NAME"),
+            std::env::var("GIT_COMMITTER_EMAIL"),
+        ) {
+            (Ok(name), Ok(email)) => git2::Signature::now(&name, &email)?,
+            _ => self.repo.signature()?,
+        };
+
+        self.repo
+            .commit(
+                Some("HEAD"),
+                &signature,
+                &signature,
+                message,
+                &tree,
+                &[&parent],
+            )
+            .map(Some)
+    }
+
+    /// Push the tag `tag_name` to the remote repository, authenticating with the given credential providers.
+    pub fn push_tag(
+        &self,
+        repository_url: &str,
+        tag_name: &str,
+        credentials: &[CredentialKind],
+    ) -> Result<(), AuthError> {
+        let tag_ref = format!("refs/tags/{}", tag_name);
+
+        self.push(
+            repository_url,
+            &format!("{}:{}", tag_ref, tag_ref),
+            credentials,
+        )
+    }
+
+    /// Push HEAD to the branch `branch` of the remote repository, authenticating with the
+    /// given credential providers.
+    pub fn push_branch(
+        &self,
+        repository_url: &str,
+        branch: &str,
+        credentials: &[CredentialKind],
+    ) -> Result<(), AuthError> {
+        self.push(
+            repository_url,
+            &format!("HEAD:refs/heads/{}", branch),
+            credentials,
+        )
+    }
+
+    /// Push `refspec` to the remote repository, reporting a reference the remote refused
+    /// to update as [`AuthError::Rejected`].
+    fn push(
+        &self,
+        repository_url: &str,
+        refspec: &str,
+        credentials: &[CredentialKind],
+    ) -> Result<(), AuthError> {
+        let mut remote = self
+            .repo
+            .remote_anonymous(repository_url)
+            .map_err(AuthError::from_git_error)?;
+
+        let rejection: RefCell<Option<String>> = RefCell::new(None);
+
+        let mut callbacks = remote_callbacks(credentials);
+
+        callbacks.push_update_reference(|_refname, status| {
+            if let Some(message) = status {
+                *rejection.borrow_mut() = Some(message.to_string());
+            }
+
+            Ok(())
+        });
+
+        let mut push_options = PushOptions::new();
+        push_options.remote_callbacks(callbacks);
+
+        remote
+            .push(&[refspec], Some(&mut push_options))
+            .map_err(AuthError::from_git_error)?;
+
+        match rejection.take() {
+            Some(reason) => Err(AuthError::Rejected(reason)),
+            None => Ok(()),
+        }
+    }
+
+    /// Get all the remote branches of the repository.
+    pub fn get_branches(&self) -> Result<Vec<String>, git2::Error> {
+        let branches = self.repo.branches(Some(git2::BranchType::Remote))?;
+
+        let branch_names = branches
+            .filter_map(|branch_result| {
+                match branch_result {
+                    Ok((branch, _)) => branch
+                        .name()
+                        .unwrap_or(None)
+                        .map(|branch_name| branch_name.to_string()),
+                    Err(_) => None, // Skip branches that result in an error
+                }
+            })
+            .collect::<Vec<String>>();
+
+        Ok(branch_names)
+    }
+
+    /// Verify if the `ref` exits.
+    pub fn is_ref_exists(&self, reference: &str) -> bool {
+        self.repo.revparse_single(reference).is_ok()
+    }
+
+    /// Get the HEAD sha.
+    pub fn get_git_head(&self) -> Result<Oid, git2::Error> {
+        self.repo
+            .head()?
+            .target()
+            .ok_or_else(|| git2::Error::from_str("HEAD is not a direct reference"))
+    }
+
+    /// Get the name of the local branch HEAD points to.
+    ///
+    /// When HEAD is detached, as in most CI checkouts, the remote branches
+    /// containing the HEAD commit are searched instead, preferring the branches
+    /// whose tip is the HEAD commit, then the ones matching `release_branches`.
+    /// Returns `None` if no branch can be found, and an error if several remain.
+    pub fn get_current_branch(
+        &self,
+        release_branches: &[String],
+    ) -> Result<Option<String>, git2::Error> {
+        let head = match self.repo.head() {
+            Ok(head) => head,
+            // No commit yet, HEAD is a symbolic reference to a branch that doesn't exist.
+            Err(e) if e.code() == ErrorCode::UnbornBranch => {
+                let head = self.repo.find_reference("HEAD")?;
+
+                return Ok(head
+                    .symbolic_target()
+                    .and_then(|target| target.strip_prefix("refs/heads/"))
+                    .map(str::to_string));
+            }
+            Err(e) => return Err(e),
+        };
+
+        if head.is_branch() {
+            return Ok(head.shorthand().map(str::to_string));
+        }
+
+        let Some(head_commit) = head.target() else {
+            return Ok(None);
+        };
+
+        let mut tips = vec![];
+        let mut containing = vec![];
+
+        for branch in self.repo.branches(Some(git2::BranchType::Remote))? {
+            let (branch, _) = branch?;
+
+            let (Some(name), Some(tip)) = (branch.name()?, branch.get().target()) else {
+                continue;
+            };
+
+            // Skip symbolic references such as `origin/HEAD`.
+            if branch.get().symbolic_target().is_some() || name.ends_with("/HEAD") {
+                continue;
+            }
+
+            let name = name.split_once('/').map_or(name, |(_, name)| name);
+
+            if tip == head_commit {
+                tips.push(name.to_string());
+            } else if self.repo.graph_descendant_of(tip, head_commit)? {
+                containing.push(name.to_string());
+            }
+        }
+
+        let mut candidates = if tips.is_empty() { containing } else { tips };
+
+        // The same branch can be on several remotes.
+        candidates.sort();
+        candidates.dedup();
+
+        let is_release_branch = |name: &String| {
+            release_branches
+                .iter()
+                .any(|pattern| branches::matches(pattern, name))
+        };
+
+        if candidates.iter().any(is_release_branch) {
+            candidates.retain(is_release_branch);
+        }
+
+        match candidates.as_slice() {
+            [] => Ok(None),
+            [name] => Ok(Some(name.clone())),
+            names => Err(git2::Error::from_str(&format!(
+                "the HEAD commit is on several branches: {}",
+                names.join(", ")
+            ))),
+        }
+    }
+
+    /// Get the repository remote URL.
+    pub fn get_repository_url(&self) -> Result<String, git2::Error> {
+        self.repo.config()?.get_string("remote.origin.url")
+    }
+
+    /// Verify write access authorization to remote repository, authenticating
+    /// with the given credential providers.
+    ///
+    /// The check never alters the remote: it only opens a push connection and lists the
+    /// remote references, which hosts only allow to users with write access. Rules
+    /// protecting `branch` can't be checked without pushing to it, so a push may still
+    /// be rejected, and the check fails with [`AuthError::Auth`] or [`AuthError::Network`]
+    /// only. When `branch` doesn't exist on the remote, the access is reported as
+    /// [`PushAccess::Unverified`].
+    pub fn verify_auth(
+        &self,
+        repository_url: &str,
+        branch: &str,
+        credentials: &[CredentialKind],
+    ) -> Result<PushAccess, AuthError> {
+        let mut remote = self
+            .repo
+            .remote_anonymous(repository_url)
+            .map_err(AuthError::from_git_error)?;
+
+        let branch_ref = format!("refs/heads/{}", branch);
+
+        let connection = remote
+            .connect_auth(Direction::Push, Some(remote_callbacks(credentials)), None)
+            .map_err(AuthError::from_git_error)?;
+
+        let heads = connection.list().map_err(AuthError::from_git_error)?;
+
+        if heads.iter().any(|head| head.name() == branch_ref) {
+            Ok(PushAccess::Verified)
+        } else {
+            Ok(PushAccess::Unverified(format!(
+                "the branch {} doesn't exist on the remote",
+                branch
+            )))
+        }
+    }
+}
+
+/// Format a git `time` in the RFC 3339 format, in its own time zone.
+fn format_time(time: Time) -> String {
+    let offset = time.offset_minutes();
+    let seconds = time.seconds() + i64::from(offset) * 60;
+
+    let (year, month, day) = civil_from_days(seconds.div_euclid(86400));
+    let seconds = seconds.rem_euclid(86400);
+
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}{}{:02}:{:02}",
+        year,
+        month,
+        day,
+        seconds / 3600,
+        seconds % 3600 / 60,
+        seconds % 60,
+        if offset < 0 { '-' } else { '+' },
+        offset.abs() / 60,
+        offset.abs() % 60
+    )
+}
+
+fn open_repo() -> GitRepo {
+    match GitRepo::open_from_env() {
+        Ok(repo) => repo,
+        Err(e) => panic!("failed to open repository: {}", e),
+    }
+}
 
 /// Get the commit **SHA** for a given tag.
 ///
@@ -11,21 +784,15 @@ use git2::{Cred, Direction, ObjectType, Oid, PushOptions, RemoteCallbacks, Repos
 ///
 /// # Example
 ///
-/// ```
+/// ```no_run
+/// # use semantic_release::git::get_tag_head;
 /// get_tag_head("v0.0.5");
 /// ```
 pub fn get_tag_head(tag_name: &str) -> Oid {
-    let repo = match Repository::open_from_env() {
-        Ok(repo) => repo,
-        Err(e) => panic!("failed to clone: {}", e),
-    };
-
-    let object = match repo.revparse_single(tag_name) {
-        Ok(object) => object,
+    match open_repo().get_tag_head(tag_name) {
+        Ok(oid) => oid,
         Err(e) => panic!("failed to get reference: {}", e),
-    };
-
-    object.id()
+    }
 }
 
 /// Get all the tags for a given branch.
@@ -37,56 +804,17 @@ pub fn get_tag_head(tag_name: &str) -> Oid {
 ///
 /// # Example
 ///
-/// ```
+/// ```no_run
+/// # use semantic_release::git::get_tags;
 /// get_tags("origin/release-v0.0.15");
 /// ```
 pub fn get_tags(branch: &str) -> Vec<String> {
-    let mut tags = Vec::new();
-
-    let repo = match Repository::open_from_env() {
-        Ok(repo) => repo,
-        Err(e) => panic!("failed to clone: {}", e),
-    };
-
-    let branch_ref = match repo.find_branch(branch, git2::BranchType::Remote) {
-        Ok(branch) => branch,
-        Err(e) => panic!("failed to find branch: {}", e),
-    };
-
-    let branch_commit = branch_ref
-        .get()
-        .peel_to_commit()
-        .expect("failed to get last commit");
-
-    repo.tag_foreach(|tag_id, name| {
-        if let Ok(object) = repo.find_object(tag_id, Some(ObjectType::Any)) {
-            let name = from_utf8(name).expect("Couldn\'t parse tag name");
-
-            if let Some(lightweight_tag) = object.as_commit() {
-                if repo
-                    .graph_descendant_of(branch_commit.id(), lightweight_tag.id())
-                    .unwrap()
-                {
-                    tags.push(name.to_string());
-                }
-            } else if let Some(annotated_tag) = object.as_tag() {
-                let target = annotated_tag
-                    .target()
-                    .expect("Couldn\'t obtain tag's target");
-
-                if target.id() == branch_commit.id() {
-                    tags.push(name.to_string());
-                }
-            }
-        }
-        true
-    })
-    .expect("Couldn\'t parse tags in the repository");
-
-    tags
+    open_repo()
+        .get_tags(branch)
+        .expect("Couldn\'t parse tags in the repository")
 }
 
-/// Retrieve a range of commits.
+/// Retrieve a range of commits, from the root commits when `from` is `None`.
 ///
 /// # Panics
 ///
@@ -96,44 +824,23 @@ pub fn get_tags(branch: &str) -> Vec<String> {
 ///
 /// # Example
 ///
-/// ```
+/// ```no_run
+/// # use semantic_release::git::get_commits;
 /// get_commits(
-///    "0779705ecc46cbced5059bcbadee7b8d254d4300",
+///    Some("0779705ecc46cbced5059bcbadee7b8d254d4300"),
 ///    "3d92276063e6ebb33d63e2d20bf23d405f9d4925",
 /// );
+/// get_commits(None, "3d92276063e6ebb33d63e2d20bf23d405f9d4925");
 /// ```
-pub fn get_commits(from: &str, to: &str) -> Vec<String> {
-    let repo = match Repository::open_from_env() {
-        Ok(repo) => repo,
-        Err(e) => panic!("failed to clone: {}", e),
-    };
-
-    let mut revwalk = repo.revwalk().expect("Couldn\'t retrieve revwalk");
-
-    let from = Oid::from_str(from)
-        .unwrap_or_else(|_| panic!("from parameter \"{:?}\" is not a valid SHA", from));
-
-    let to = Oid::from_str(to)
-        .unwrap_or_else(|_| panic!("to parameter \"{:?}\" is not a valid SHA", to));
-
-    revwalk
-        .push(to)
-        .unwrap_or_else(|_| panic!("Couldn\'t set revwalk root to commit \"{:?}\"", to));
-
-    revwalk
-        .hide(from)
-        .unwrap_or_else(|_| panic!("Couldn\'t hide commit \"{:?}\"", from));
-
-    let mut commits = Vec::new();
-
-    for oid in revwalk.flatten() {
-        let commit = repo.find_commit(oid).expect("Couldn\'t find commit");
-        if let Some(message) = commit.message() {
-            commits.push(message.to_string());
-        }
-    }
-
-    commits
+pub fn get_commits(from: Option<&str>, to: &str) -> Vec<String> {
+    open_repo().get_commits(from, to).unwrap_or_else(|e| {
+        panic!(
+            "Couldn\'t retrieve commits from {} to {}: {}",
+            from.unwrap_or("the root commits"),
+            to,
+            e
+        )
+    })
 }
 
 /// Get all the repository branches.
@@ -145,32 +852,14 @@ pub fn get_commits(from: &str, to: &str) -> Vec<String> {
 ///
 /// # Example
 ///
-/// ```
+/// ```no_run
+/// # use semantic_release::git::get_branches;
 /// get_branches();
 /// ```
 pub fn get_branches() -> Vec<String> {
-    let repo = match Repository::open_from_env() {
-        Ok(repo) => repo,
-        Err(e) => panic!("failed to clone: {}", e),
-    };
-
-    let branches = repo
-        .branches(Some(git2::BranchType::Remote))
-        .expect("Couldn\'t retrieve any branches for this repository.");
-
-    let branch_names = branches
-        .filter_map(|branch_result| {
-            match branch_result {
-                Ok((branch, _)) => branch
-                    .name()
-                    .unwrap_or(None)
-                    .map(|branch_name| branch_name.to_string()),
-                Err(_) => None, // Skip branches that result in an error
-            }
-        })
-        .collect::<Vec<String>>();
-
-    branch_names
+    open_repo()
+        .get_branches()
+        .expect("Couldn\'t retrieve any branches for this repository.")
 }
 
 /// Verify if the `ref` exits
@@ -181,18 +870,12 @@ pub fn get_branches() -> Vec<String> {
 ///
 /// # Example
 ///
-/// ```
+/// ```no_run
+/// # use semantic_release::git::is_ref_exists;
 /// is_ref_exists("origin/release-v0.0.15");
 /// ```
 pub fn is_ref_exists(reference: &str) -> bool {
-    let repo = match Repository::open_from_env() {
-        Ok(repo) => repo,
-        Err(e) => panic!("failed to clone: {}", e),
-    };
-
-    let exists = repo.revparse_single(reference).is_ok();
-
-    exists
+    open_repo().is_ref_exists(reference)
 }
 
 /// Get the HEAD sha.
@@ -203,20 +886,14 @@ pub fn is_ref_exists(reference: &str) -> bool {
 ///
 /// # Example
 ///
-/// ```
+/// ```no_run
+/// # use semantic_release::git::get_git_head;
 /// get_git_head();
 /// ```
 pub fn get_git_head() -> Oid {
-    let repo = match Repository::open_from_env() {
-        Ok(repo) => repo,
-        Err(e) => panic!("failed to clone: {}", e),
-    };
-
-    let head = repo
-        .head()
-        .expect("Couldn\'t get HEAD ref for this repository");
-
-    head.target().expect("Couldn't get the target OID")
+    open_repo()
+        .get_git_head()
+        .expect("Couldn\'t get HEAD ref for this repository")
 }
 
 /// Get the repository remote URL.
@@ -227,19 +904,13 @@ pub fn get_git_head() -> Oid {
 ///
 /// # Example
 ///
-/// ```
+/// ```no_run
+/// # use semantic_release::git::get_repository_url;
 /// get_repository_url();
 /// ```
 pub fn get_repository_url() -> String {
-    let repo = match Repository::open_from_env() {
-        Ok(repo) => repo,
-        Err(e) => panic!("failed to clone: {}", e),
-    };
-
-    let config = repo.config().expect("Couldn\'t get the repository config");
-
-    config
-        .get_string("remote.origin.url")
+    open_repo()
+        .get_repository_url()
         .expect("Couldn\'t get the remote origin url")
 }
 
@@ -247,72 +918,292 @@ pub fn get_repository_url() -> String {
 ///
 /// # Example
 ///
-/// ```
+/// ```no_run
+/// # use semantic_release::git::is_git_repo;
 /// is_git_repo();
 /// ```
 pub fn is_git_repo() -> bool {
-    Repository::open_from_env().is_ok()
+    GitRepo::open_from_env().is_ok()
 }
 
-/// Verify write access authorization to remote repository with
-/// push dry-run.
+/// Verify write access authorization to remote repository, authenticating
+/// with the given credential providers.
+///
+/// See [`GitRepo::verify_auth`].
+///
 /// # Panics
 ///
-/// Will panic if no repository is found in current directory or any of the parents, cannot get config or cannot create a remote or cannot connect.
+/// Will panic if no repository is found in current directory or any of the parents.
 ///
 /// # Example
 ///
+/// ```no_run
+/// # use semantic_release::git::verify_auth;
+/// # use semantic_release::credentials::default_credentials;
+/// verify_auth(
+///     "git@github.com:Javimtib92/papyrust.git",
+///     "main",
+///     &default_credentials(),
+/// );
 /// ```
-/// verify_auth("git@github.com:Javimtib92/papyrust.git", "main");
-/// ```
-pub fn verify_auth(repository_url: &str, branch: &str) -> bool {
-    // Open the repository in the current directory
-    let repo = match Repository::open_from_env() {
-        Ok(repo) => repo,
-        Err(e) => panic!("failed to clone: {}", e),
-    };
-
-    let mut remote = repo
-        .remote_anonymous(repository_url)
-        .expect("Couldn\'t create a remote with the provided URL");
-
-    // Set up callbacks for authentication
-    let mut callbacks = RemoteCallbacks::new();
-
-    let mut ssh_attempts_count = 0;
-
-    callbacks.credentials(|_url, username, allowed| {
-        if allowed.contains(git2::CredentialType::SSH_KEY) {
-            // If ssh-agent authentication fails, libgit2 will keep
-            // calling this callback asking for other authentication
-            // methods to try. Make sure we only try ssh-agent once.
-            ssh_attempts_count += 1;
-            // dbg!(self.ssh_attempts_count);
-            let u = username.unwrap_or("git");
-            return if ssh_attempts_count == 1 {
-                Cred::ssh_key_from_agent(u)
-            } else {
-                Err(git2::Error::from_str("try with an other username"))
-            };
-        }
+pub fn verify_auth(
+    repository_url: &str,
+    branch: &str,
+    credentials: &[CredentialKind],
+) -> Result<PushAccess, AuthError> {
+    open_repo().verify_auth(repository_url, branch, credentials)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_auth_error_classification() {
+        let classify = |code, class, message| match AuthError::from_git_error(git2::Error::new(
+            code, class, message,
+        )) {
+            AuthError::Auth(_) => "auth",
+            AuthError::Network(_) => "network",
+            AuthError::Rejected(_) => "rejected",
+        };
 
-        if allowed.contains(git2::CredentialType::DEFAULT) {
-            return git2::Cred::default();
+        assert_eq!(
+            classify(
+                ErrorCode::Auth,
+                ErrorClass::Ssh,
+                "failed to authenticate SSH session"
+            ),
+            "auth"
+        );
+        assert_eq!(
+            classify(
+                ErrorCode::GenericError,
+                ErrorClass::Ssh,
+                "failed to start SSH session: Unable to exchange encryption keys"
+            ),
+            "network"
+        );
+        assert_eq!(
+            classify(
+                ErrorCode::GenericError,
+                ErrorClass::Http,
+                "unexpected http status code: 403"
+            ),
+            "auth"
+        );
+        assert_eq!(
+            classify(
+                ErrorCode::GenericError,
+                ErrorClass::Net,
+                "failed to resolve address for github.com: Name or service not known"
+            ),
+            "network"
+        );
+    }
+
+    /// Commit the `files` of the work tree of `repo`, each containing `message`, on top of `parents`.
+    fn commit(repo: &Repository, files: &[&str], message: &str, parents: &[Oid]) -> Oid {
+        let dir = repo.workdir().unwrap();
+        let parents: Vec<git2::Commit> = parents
+            .iter()
+            .map(|oid| repo.find_commit(*oid).unwrap())
+            .collect();
+        let mut index = repo.index().unwrap();
+
+        // Start from the first parent, for the commits of other branches.
+        match parents.first() {
+            Some(parent) => index.read_tree(&parent.tree().unwrap()).unwrap(),
+            None => index.clear().unwrap(),
         }
 
-        Err(git2::Error::from_str("no valid authentication available"))
-    });
+        for file in files {
+            let path = dir.join(file);
+
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(&path, message).unwrap();
+            index.add_path(Path::new(file)).unwrap();
+        }
 
-    let mut push_options = PushOptions::new();
+        index.write().unwrap();
 
-    remote
-        .connect_auth(Direction::Push, Some(callbacks), None)
-        .expect("Couldn't connect");
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let signature =
+            git2::Signature::new("Jane", "jane@example.com", &Time::new(1704105000, 120)).unwrap();
 
-    remote
-        .push(
-            &[format!("HEAD:refs/heads/{}", branch)],
-            Some(&mut push_options),
+        repo.commit(
+            None,
+            &signature,
+            &signature,
+            message,
+            &tree,
+            &parents.iter().collect::<Vec<&git2::Commit>>(),
         )
-        .is_ok()
+        .unwrap()
+    }
+
+    #[test]
+    fn test_detached_head_branch() {
+        let dir =
+            std::env::temp_dir().join(format!("semantic-release-head-{}", std::process::id()));
+        let repo = Repository::init(&dir).unwrap();
+
+        let init = commit(&repo, &["README.md"], "chore: init", &[]);
+        let fix = commit(&repo, &["src/lib.rs"], "fix: fix", &[init]);
+
+        for (name, target) in [
+            ("origin/main", fix),
+            ("upstream/main", fix),
+            ("origin/feature", fix),
+        ] {
+            repo.reference(&format!("refs/remotes/{}", name), target, true, "")
+                .unwrap();
+        }
+
+        repo.set_head_detached(init).unwrap();
+
+        let git_repo = GitRepo::open(&dir).unwrap();
+        let branches = |patterns: &[&str]| {
+            let patterns: Vec<String> = patterns.iter().map(|name| name.to_string()).collect();
+
+            git_repo.get_current_branch(&patterns)
+        };
+
+        let main = branches(&["main"]).unwrap();
+        let ambiguous = branches(&["next"]).is_err();
+
+        repo.reference("refs/remotes/origin/feature", init, true, "")
+            .unwrap();
+
+        let tip = branches(&["main"]).unwrap();
+
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(main.as_deref(), Some("main"));
+        assert!(ambiguous);
+        assert_eq!(tip.as_deref(), Some("feature"));
+    }
+
+    #[test]
+    fn test_reachable_tags() {
+        let dir =
+            std::env::temp_dir().join(format!("semantic-release-tags-{}", std::process::id()));
+        let repo = Repository::init(&dir).unwrap();
+
+        let init = commit(&repo, &["README.md"], "chore: init", &[]);
+        let fix = commit(&repo, &["src/lib.rs"], "fix: fix", &[init]);
+        let tree = repo.find_commit(fix).unwrap().tree_id();
+
+        for (name, target) in [("v1.0.0", init), ("v1.0.1", fix), ("v1.0.1-tree", tree)] {
+            repo.tag_lightweight(name, &repo.find_object(target, None).unwrap(), false)
+                .unwrap();
+        }
+
+        let git_repo = GitRepo::open(&dir).unwrap();
+        let tags = git_repo.get_reachable_tags(init).unwrap();
+
+        git_repo.delete_tag("v1.0.0").unwrap();
+
+        let remaining = git_repo.get_reachable_tags(fix).unwrap();
+
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(tags, [("v1.0.0".to_string(), init)]);
+        assert_eq!(remaining, [("v1.0.1".to_string(), fix)]);
+    }
+
+    #[test]
+    fn test_query_commits() {
+        let dir = std::env::temp_dir().join(format!("semantic-release-git-{}", std::process::id()));
+        let repo = Repository::init(&dir).unwrap();
+
+        let init = commit(&repo, &["README.md"], "chore: init", &[]);
+        repo.tag_lightweight("v1.0.0", &repo.find_object(init, None).unwrap(), false)
+            .unwrap();
+
+        let core = commit(&repo, &["crates/core/lib.rs"], "feat(core): add", &[init]);
+        let cli = commit(&repo, &["crates/cli/main.rs"], "fix(cli): fix", &[init]);
+        let merge = commit(&repo, &[], "Merge branch 'cli'", &[core, cli]);
+
+        let git_repo = GitRepo::open(&dir).unwrap();
+        let query = |paths: &[&str], exclude_merges: bool| {
+            git_repo
+                .query_commits(&CommitQuery {
+                    to: Some(merge),
+                    since_tag: Some("v1.0.0".to_string()),
+                    paths: paths.iter().map(PathBuf::from).collect(),
+                    exclude_merges,
+                    ..CommitQuery::default()
+                })
+                .unwrap()
+                .iter()
+                .map(|commit| commit.message.clone())
+                .collect::<Vec<String>>()
+        };
+
+        assert_eq!(query(&[], false).len(), 3);
+        assert_eq!(query(&[], true).len(), 2);
+        assert_eq!(query(&["crates/core"], true), ["feat(core): add"]);
+
+        let with_files = |files: bool| {
+            git_repo
+                .query_commits(&CommitQuery {
+                    to: Some(core),
+                    since_tag: Some("v1.0.0".to_string()),
+                    files,
+                    ..CommitQuery::default()
+                })
+                .unwrap()[0]
+                .files
+                .clone()
+        };
+
+        assert!(with_files(false).is_empty());
+        assert_eq!(with_files(true), [PathBuf::from("crates/core/lib.rs")]);
+        assert!(git_repo
+            .commit_changes_paths(core, &[Path::new("crates/core")])
+            .unwrap());
+        assert!(!git_repo
+            .commit_changes_paths(core, &[Path::new("crates/cli")])
+            .unwrap());
+
+        let root = git_repo
+            .query_commits(&CommitQuery {
+                to: Some(merge),
+                since_tag: Some("v1.0.0".to_string()),
+                exclude_paths: vec!["crates/cli".into()],
+                exclude_merges: true,
+                ..CommitQuery::default()
+            })
+            .unwrap();
+
+        assert_eq!(root.len(), 1);
+        assert_eq!(root[0].message, "feat(core): add");
+
+        // A second root, merged into the history.
+        let docs = commit(&repo, &["docs/index.md"], "docs: init", &[]);
+        let merge_docs = commit(&repo, &[], "Merge branch 'docs'", &[merge, docs]);
+
+        let all = git_repo.get_commits(None, &merge_docs.to_string()).unwrap();
+
+        assert_eq!(all.len(), 6);
+        assert!(all.contains(&"chore: init".to_string()));
+        assert!(all.contains(&"docs: init".to_string()));
+
+        let commit = git_repo.get_commit(core).unwrap();
+
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(commit.short_hash, &commit.hash[..7]);
+        assert_eq!(commit.parents, [init.to_string()]);
+        assert_eq!(commit.files, [PathBuf::from("crates/core/lib.rs")]);
+        assert_eq!(
+            commit.author,
+            Signature {
+                name: "Jane".to_string(),
+                email: "jane@example.com".to_string(),
+                date: "2024-01-01T12:30:00+02:00".to_string(),
+            }
+        );
+    }
 }
@@ -1,7 +1,24 @@
+pub mod analyze_commits;
+pub mod branches;
+pub mod ci;
+pub mod cli;
+pub mod config_env;
 pub mod context;
+pub mod credentials;
+pub mod error;
+pub mod extends;
+pub mod generate_notes;
 pub mod get_config;
 pub mod get_git_auth_url;
 pub mod get_git_hosted_info;
 pub mod git;
 pub mod hide_sensitive;
+pub mod logger;
+pub mod outputs;
+pub mod packages;
+pub mod plugins;
+pub mod publish;
+pub mod release;
+pub mod report;
+pub mod validate_config;
 pub mod verify_context;
@@ -1,40 +1,237 @@
+use std::{
+    collections::BTreeMap,
+    fs::{File, OpenOptions},
+    io::IsTerminal,
+    path::Path,
+    process::ExitCode,
+};
+
+use clap::Parser;
+
 use semantic_release::{
-    context::Context, get_config::get_config, get_git_auth_url::get_git_auth_url,
+    branches,
+    ci::detect_ci_env,
+    cli::{Cli, Command, OutputFormat},
+    context::Context,
+    error::{AggregateError, SemanticReleaseError},
+    extends::ConfigSource,
+    get_config::{find_config_file, load_config, Config},
+    get_git_auth_url::get_git_auth_url,
+    git::{GitRepo, PushAccess},
+    hide_sensitive::{hide_sensitive_with, SecretsConfig},
+    logger::Logger,
+    outputs::{verify_output_prefixes, write_dotenv, write_github_output, ReleaseOutputs},
+    packages::get_packages,
+    publish::publish_release,
+    release::{plan_package_releases, plan_release, write_summary},
+    report::{exit_code, write_report},
+    validate_config::{config_schema, validate_config},
     verify_context::verify_context,
 };
 
 const COMMIT_NAME: &str = "semantic-release-bot";
 const COMMIT_EMAIL: &str = "javimtib92@gmail.com";
 
-fn main() {
-    let github_actions = std::env::var("GITHUB_ACTIONS");
-    let github_event_name = std::env::var("GITHUB_EVENT_NAME");
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+
+    let ci_env = detect_ci_env();
+
+    let cwd = std::env::current_dir().expect("Failed to get current directory");
 
-    let is_ci = github_actions.is_ok();
+    match &cli.command {
+        Some(Command::Validate { path }) => {
+            return validate(&cwd, path.as_deref().or(cli.config.as_deref()))
+        }
+        Some(Command::Schema) => {
+            let schema =
+                serde_json::to_string_pretty(&config_schema()).expect("Schema should serialize");
 
-    let is_pr = github_event_name
-        .map(|value| value == "pull_request" || value == "pull_request_target")
-        .unwrap_or(false);
+            println!("{}", schema);
 
-    let branch = match is_pr {
-        true => std::env::var("GITHUB_HEAD_REF").expect("Couldnt\'t get GITHUB_HEAD_REF"),
-        false => std::env::var("GITHUB_REF").expect("Couldnt\'t get GITHUB_REF"),
+            return ExitCode::SUCCESS;
+        }
+        None => {}
+    }
+
+    let loaded = match load_config(&cwd, cli.config.as_deref(), cli.extends.as_deref()) {
+        Ok(loaded) => loaded,
+        Err(error) => return report_errors(&[error], &SecretsConfig::default()),
     };
 
-    let config = get_config().expect("Couldn\'t get config file");
+    let json = cli.output == OutputFormat::Json;
+
+    let mut config = loaded.config;
+
+    cli.apply(&mut config);
+
+    // `ci` defaults to `false` outside of CI, so only an explicit value allows publishing from there.
+    let no_ci = !config.ci && (cli.no_ci || loaded.provenance.contains_key("ci"));
+
+    // Stdout is reserved for the outputs in JSON mode.
+    let logger = Logger::terminal(
+        json,
+        config.debug,
+        hide_sensitive_with(std::env::vars(), &config.secrets),
+    );
+
+    match &loaded.file_path {
+        Some(path) => logger.info(format!("Loaded config from {}", path.display())),
+        None => logger.info("No config file found, using the default configuration"),
+    }
+
+    for name in &loaded.undefined_vars {
+        logger.warn(format!(
+            "The environment variable {} used in the config isn\'t set, it expands to an empty string",
+            name
+        ));
+    }
+
+    if logger.is_debug() {
+        log_config(&loaded.sources, &loaded.provenance, &config, &logger);
+    }
+
+    let repo = GitRepo::open(&cwd).ok();
+
+    // Outside of CI, or when the service doesn't report it, use the branch checked out locally.
+    let branch = match (&ci_env.branch, &ci_env.tag, &repo) {
+        (Some(branch), _, _) => branch.clone(),
+        (None, None, Some(repo)) => match repo.get_current_branch(&config.branches) {
+            Ok(branch) => branch.unwrap_or_default(),
+            Err(error) => {
+                logger.warn(format!(
+                    "Couldn\'t tell which branch is checked out: {}",
+                    error.message()
+                ));
+
+                String::new()
+            }
+        },
+        _ => String::new(),
+    };
 
     let mut context = Context {
-        is_ci,
-        is_pr,
+        cwd,
+        is_ci: ci_env.is_ci,
+        is_pr: ci_env.is_pr,
         branch,
         config,
+        ci_env,
+        logger,
+    };
+
+    let outputs = match run(&mut context, repo, no_ci) {
+        Ok(outputs) => outputs,
+        Err(errors) => return report_errors(errors.errors(), &context.config.secrets),
     };
 
-    run(&mut context);
+    if json {
+        let outputs = serde_json::to_string_pretty(&outputs).expect("Outputs should serialize");
+
+        println!("{}", outputs);
+    }
+
+    let mut errors = Vec::new();
+
+    if let Ok(path) = std::env::var("GITHUB_OUTPUT") {
+        errors.extend(append_outputs(Path::new(&path), &outputs, write_github_output).err());
+    }
+
+    if let Some(path) = &cli.output_file {
+        errors.extend(append_outputs(path, &outputs, write_dotenv).err());
+    }
+
+    if !errors.is_empty() {
+        return report_errors(&errors, &context.config.secrets);
+    }
+
+    ExitCode::SUCCESS
+}
+
+/// Append the release `outputs` to the file at `path`, in the format of `write`.
+fn append_outputs(
+    path: &Path,
+    outputs: &ReleaseOutputs,
+    write: fn(&mut File, &ReleaseOutputs) -> std::io::Result<()>,
+) -> Result<(), SemanticReleaseError> {
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .and_then(|mut file| write(&mut file, outputs))
+        .map_err(|e| SemanticReleaseError::WriteOutputs {
+            path: path.to_path_buf(),
+            reason: e.to_string(),
+        })
 }
 
-fn run(context: &mut Context) {
-    if !context.is_ci && !context.config.dry_run && !context.config.ci {
+/// Validate the config file at `config_path`, or the one found from `cwd`, and report every problem.
+fn validate(cwd: &Path, config_path: Option<&Path>) -> ExitCode {
+    let file_path = match config_path {
+        Some(path) => path.to_path_buf(),
+        None => match find_config_file(cwd) {
+            Ok(Some(path)) => path,
+            Ok(None) => {
+                println!("No config file found, the default configuration is used");
+
+                return ExitCode::SUCCESS;
+            }
+            Err(error) => return report_errors(&[error], &SecretsConfig::default()),
+        },
+    };
+
+    match validate_config(cwd, &file_path) {
+        Ok(()) => {
+            println!("{} is valid", file_path.display());
+
+            ExitCode::SUCCESS
+        }
+        Err(errors) => report_errors(errors.errors(), &SecretsConfig::default()),
+    }
+}
+
+/// Log where the config was loaded from and the resulting options.
+fn log_config(
+    sources: &[ConfigSource],
+    provenance: &BTreeMap<String, String>,
+    config: &Config,
+    logger: &Logger,
+) {
+    if sources.len() > 1 {
+        logger.debug("Merged config sources, in order:");
+
+        for source in sources {
+            logger.debug(format!("  {}", source));
+        }
+    }
+
+    for (option, source) in provenance {
+        logger.debug(format!("{} set by {}", option, source));
+    }
+
+    let config = serde_json::to_string_pretty(config).expect("Config should serialize");
+
+    logger.debug(format!("Config: {}", config));
+}
+
+/// Print the errors with their details to stderr, masking any secret, and
+/// return the exit code matching their category.
+fn report_errors(errors: &[SemanticReleaseError], secrets: &SecretsConfig) -> ExitCode {
+    let hide_sensitive_fn = hide_sensitive_with(std::env::vars(), secrets);
+    let color = std::io::stderr().is_terminal();
+
+    write_report(&mut std::io::stderr(), errors, hide_sensitive_fn, color)
+        .expect("Couldn\'t write the error report");
+
+    ExitCode::from(exit_code(errors))
+}
+
+fn run(
+    context: &mut Context,
+    repo: Option<GitRepo>,
+    no_ci: bool,
+) -> Result<ReleaseOutputs, AggregateError> {
+    if !context.is_ci && !context.config.dry_run && !no_ci {
         // This run was not triggered in a known CI environment, running in dry-run mode.
         context.config.dry_run = true;
     } else {
@@ -48,23 +245,106 @@ fn run(context: &mut Context) {
         std::env::set_var("GIT_TERMINAL_PROMPT", "0");
     }
 
-    if context.is_ci && context.is_pr && !context.config.ci {
-        // This run was triggered by a pull request and therefore a new version won't be published.
-        return;
+    if context.is_ci && context.is_pr && context.config.ci {
+        context.logger.info(
+            "This run was triggered by a pull request and therefore a new version won’t be published.",
+        );
+
+        return Ok(ReleaseOutputs::default());
+    }
+
+    if let Some(tag) = &context.ci_env.tag {
+        context.logger.info(format!(
+            "This run was triggered by the tag {}, therefore a new version won’t be published.",
+            tag
+        ));
+
+        return Ok(ReleaseOutputs::default());
     }
 
-    verify_context(context).expect("Context is not valid");
+    verify_context(context, repo.as_ref())?;
+
+    let repo = repo.expect("Verified context should have a git repository");
+
+    let (repository_url, access) = get_git_auth_url(context, &repo)?;
 
-    context.config.repository_url = get_git_auth_url(context);
+    context.config.repository_url = repository_url;
+
+    if let PushAccess::Unverified(reason) = access {
+        context.logger.warn(format!(
+            "Couldn\'t verify the push access to the branch {}: {}",
+            context.branch, reason
+        ));
+    }
+
+    let is_release_branch = context
+        .config
+        .branches
+        .iter()
+        .any(|pattern| branches::matches(pattern, &context.branch));
+
+    if !is_release_branch {
+        context.logger.info(format!(
+            "This test run was triggered on the branch {}, while semantic-release is configured to only publish from {}, therefore a new version won’t be published.", context.branch, context.config.branches.join(", ")));
+
+        return Ok(ReleaseOutputs::default());
+    }
+
+    let plans = if context.config.monorepo.enabled {
+        let packages = get_packages(&context.cwd, &context.config.monorepo)?;
+
+        let errors = verify_output_prefixes(&packages);
+
+        if !errors.is_empty() {
+            return Err(AggregateError::new(errors));
+        }
+
+        if packages.is_empty() {
+            context
+                .logger
+                .warn("No packages found, therefore a new version won’t be published.");
+        }
+
+        plan_package_releases(context, &repo, &packages)?
+    } else {
+        vec![plan_release(context, &repo, None, &[], &[])?]
+    };
+
+    if context.config.dry_run {
+        context
+            .logger
+            .warn("Dry run, the release isn\'t published.");
+
+        for plan in &plans {
+            let mut summary = Vec::new();
+
+            write_summary(&mut summary, plan, context.logger.is_color())
+                .expect("Couldn\'t write the release summary");
+
+            context.logger.info(String::from_utf8_lossy(&summary));
+        }
+
+        return Ok(ReleaseOutputs::new(&plans, false));
+    }
 
-    context.config.branches = vec!["todo".to_owned(), "todo".to_owned()];
+    for plan in &plans {
+        let logger = match &plan.package {
+            Some(package) => context.logger.scope(&package.name),
+            None => context.logger.clone(),
+        };
 
-    if context.config.branches.contains(&context.branch) {
-        println!(
-            "This test run was triggered on the branch {}, while semantic-release is configured to only publish from {}, therefore a new version won’t be published.", context.branch, context.config.branches.join(", "));
+        match &plan.next_release {
+            Some(next_release) => {
+                publish_release(context, &repo, plan)?;
 
-        return;
+                logger.success(format!(
+                    "Published release {} with the tag {}",
+                    next_release.version, next_release.git_tag
+                ));
+            }
+            None => logger.info("There are no relevant changes, so no new version is released."),
+        }
     }
 
-    println!("continue");
+    Ok(ReleaseOutputs::new(&plans, true))
 }
@@ -0,0 +1,287 @@
+use std::{
+    collections::BTreeMap,
+    io::{self, Write},
+};
+
+use serde::Serialize;
+
+use crate::{error::SemanticReleaseError, packages::Package, release::ReleasePlan};
+
+/// The results of a run, for the other steps of a pipeline.
+#[derive(Debug, Default, PartialEq, Eq, Serialize)]
+pub struct ReleaseOutputs {
+    /// Whether a release was published, always `false` in dry-run mode.
+    pub new_release_published: bool,
+    pub new_release_version: Option<String>,
+    pub new_release_git_tag: Option<String>,
+    pub new_release_channel: Option<String>,
+    pub new_release_notes: Option<String>,
+    pub last_release_version: Option<String>,
+    /// The outputs of each package by name, in monorepo mode.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub packages: BTreeMap<String, ReleaseOutputs>,
+}
+
+impl ReleaseOutputs {
+    /// Get the outputs of a run that planned `plans`, one per package in monorepo mode.
+    ///
+    /// In monorepo mode, a release is published when any package is.
+    pub fn new(plans: &[ReleasePlan], published: bool) -> Self {
+        match plans {
+            [plan] if plan.package.is_none() => ReleaseOutputs::from_plan(plan, published),
+            plans => {
+                let packages: BTreeMap<String, ReleaseOutputs> = plans
+                    .iter()
+                    .filter_map(|plan| {
+                        let package = plan.package.as_ref()?;
+
+                        Some((
+                            package.name.clone(),
+                            ReleaseOutputs::from_plan(plan, published),
+                        ))
+                    })
+                    .collect();
+
+                ReleaseOutputs {
+                    new_release_published: packages
+                        .values()
+                        .any(|outputs| outputs.new_release_published),
+                    packages,
+                    ..ReleaseOutputs::default()
+                }
+            }
+        }
+    }
+
+    fn from_plan(plan: &ReleasePlan, published: bool) -> Self {
+        let next_release = plan.next_release.as_ref();
+
+        ReleaseOutputs {
+            new_release_published: published && next_release.is_some(),
+            new_release_version: next_release.map(|release| release.version.clone()),
+            new_release_git_tag: next_release.map(|release| release.git_tag.clone()),
+            new_release_channel: next_release.and_then(|release| release.channel.clone()),
+            new_release_notes: next_release.map(|release| release.notes.clone()),
+            last_release_version: plan
+                .last_release
+                .as_ref()
+                .map(|last_release| last_release.version.clone()),
+            packages: BTreeMap::new(),
+        }
+    }
+
+    /// The outputs as key-value pairs, missing values being empty.
+    ///
+    /// The outputs of packages follow, prefixed with the package name where every
+    /// character that isn't alphanumeric is replaced with `_`, e.g. `acme_web_new_release_version`
+    /// for `@acme/web`.
+    pub fn entries(&self) -> Vec<(String, String)> {
+        let mut entries: Vec<(String, String)> = [
+            (
+                "new_release_published",
+                self.new_release_published.to_string(),
+            ),
+            (
+                "new_release_version",
+                self.new_release_version.clone().unwrap_or_default(),
+            ),
+            (
+                "new_release_git_tag",
+                self.new_release_git_tag.clone().unwrap_or_default(),
+            ),
+            (
+                "new_release_channel",
+                self.new_release_channel.clone().unwrap_or_default(),
+            ),
+            (
+                "new_release_notes",
+                self.new_release_notes.clone().unwrap_or_default(),
+            ),
+            (
+                "last_release_version",
+                self.last_release_version.clone().unwrap_or_default(),
+            ),
+        ]
+        .into_iter()
+        .map(|(key, value)| (key.to_string(), value))
+        .collect();
+
+        for (name, outputs) in &self.packages {
+            let prefix = output_prefix(name);
+
+            entries.extend(
+                outputs
+                    .entries()
+                    .into_iter()
+                    .map(|(key, value)| (format!("{}_{}", prefix, key), value)),
+            );
+        }
+
+        entries
+    }
+}
+
+/// The prefix of the outputs of the package `name`, where every character that isn't
+/// alphanumeric is replaced with `_`.
+///
+/// # Example
+///
+/// ```
+/// # use semantic_release::outputs::output_prefix;
+/// assert_eq!(output_prefix("@acme/web"), "acme_web");
+/// ```
+pub fn output_prefix(name: &str) -> String {
+    let prefix: String = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+
+    prefix.trim_matches('_').to_string()
+}
+
+/// Check that the outputs of the `packages` have distinct prefixes, e.g. not `@acme/web`
+/// and `acme-web`, returning every collision found.
+pub fn verify_output_prefixes(packages: &[Package]) -> Vec<SemanticReleaseError> {
+    let mut prefixes: BTreeMap<String, Vec<String>> = BTreeMap::new();
+
+    for package in packages {
+        prefixes
+            .entry(output_prefix(&package.name))
+            .or_default()
+            .push(package.name.clone());
+    }
+
+    prefixes
+        .into_iter()
+        .filter(|(_, packages)| packages.len() > 1)
+        .map(|(prefix, packages)| SemanticReleaseError::OutputPrefixCollision { prefix, packages })
+        .collect()
+}
+
+/// Write the outputs in the format of the `$GITHUB_OUTPUT` file of GitHub Actions.
+///
+/// Multiline values use the heredoc syntax, with a delimiter that doesn't appear in the value.
+///
+/// # Example
+///
+/// ```
+/// # use semantic_release::outputs::{write_github_output, ReleaseOutputs};
+/// let outputs = ReleaseOutputs {
+///     new_release_published: true,
+///     new_release_version: Some("1.1.0".to_string()),
+///     new_release_notes: Some("## 1.1.0\n\n* add --output".to_string()),
+///     ..ReleaseOutputs::default()
+/// };
+///
+/// let mut out = Vec::new();
+/// write_github_output(&mut out, &outputs).unwrap();
+///
+/// let output = String::from_utf8(out).unwrap();
+///
+/// assert!(output.starts_with("new_release_published=true\nnew_release_version=1.1.0\n"));
+/// assert!(output.contains("new_release_notes<<EOF\n## 1.1.0\n\n* add --output\nEOF\n"));
+/// ```
+pub fn write_github_output(out: &mut impl Write, outputs: &ReleaseOutputs) -> io::Result<()> {
+    for (key, value) in outputs.entries() {
+        if value.contains('\n') {
+            let mut delimiter = "EOF".to_string();
+
+            while value.lines().any(|line| line == delimiter) {
+                delimiter.push('_');
+            }
+
+            writeln!(out, "{}<<{}\n{}\n{}", key, delimiter, value, delimiter)?;
+        } else {
+            writeln!(out, "{}={}", key, value)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Write the outputs as a dotenv file, e.g. for the `artifacts:reports:dotenv` of GitLab CI.
+///
+/// Values are written as is, since GitLab neither unquotes nor unescapes them. It doesn't
+/// support multiline values either, so they are left out: the release notes are only
+/// available in the JSON output.
+pub fn write_dotenv(out: &mut impl Write, outputs: &ReleaseOutputs) -> io::Result<()> {
+    for (key, value) in outputs.entries() {
+        if !value.contains('\n') {
+            writeln!(out, "{}={}", key, value)?;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_dotenv() {
+        let outputs = ReleaseOutputs {
+            new_release_published: false,
+            new_release_version: Some("2.0.0".to_string()),
+            new_release_notes: Some("## 2.0.0\n\n* \"quoted\"".to_string()),
+            new_release_channel: Some("next \"major\"".to_string()),
+            last_release_version: Some("1.4.0".to_string()),
+            ..ReleaseOutputs::default()
+        };
+
+        let mut out = Vec::new();
+        write_dotenv(&mut out, &outputs).unwrap();
+
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "new_release_published=false\n\
+             new_release_version=2.0.0\n\
+             new_release_git_tag=\n\
+             new_release_channel=next \"major\"\n\
+             last_release_version=1.4.0\n"
+        );
+    }
+
+    #[test]
+    fn test_package_entries() {
+        let outputs = ReleaseOutputs {
+            new_release_published: true,
+            packages: BTreeMap::from([(
+                "@acme/web".to_string(),
+                ReleaseOutputs {
+                    new_release_published: true,
+                    new_release_version: Some("1.2.0".to_string()),
+                    ..ReleaseOutputs::default()
+                },
+            )]),
+            ..ReleaseOutputs::default()
+        };
+
+        let entries = outputs.entries();
+
+        assert_eq!(entries.len(), 12);
+        assert!(entries.contains(&(
+            "acme_web_new_release_version".to_string(),
+            "1.2.0".to_string()
+        )));
+    }
+
+    #[test]
+    fn test_verify_output_prefixes() {
+        let package = |name: &str| Package {
+            name: name.to_string(),
+            path: name.into(),
+            dependencies: vec![],
+        };
+
+        assert!(verify_output_prefixes(&[package("@acme/web"), package("core")]).is_empty());
+
+        match verify_output_prefixes(&[package("@acme/web"), package("acme-web")]).as_slice() {
+            [SemanticReleaseError::OutputPrefixCollision { prefix, packages }] => {
+                assert_eq!(prefix, "acme_web");
+                assert_eq!(packages, &["@acme/web", "acme-web"]);
+            }
+            errors => panic!("unexpected errors {:?}", errors),
+        }
+    }
+}
@@ -0,0 +1,829 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use regex::{Captures, Regex};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use toml_edit::{DocumentMut, Item, TableLike, TomlError};
+
+use crate::{
+    branches,
+    error::SemanticReleaseError,
+    get_config::{parse, ConfigFormat},
+};
+
+/// Release configuration of monorepos, where packages are released independently.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct MonorepoConfig {
+    /// Whether each package of the repository gets its own releases.
+    pub enabled: bool,
+    /// Format of the tags of package releases, with the `${name}` and `${version}` variables.
+    #[serde(alias = "tagFormat")]
+    pub tag_format: String,
+    /// The packages to release. When empty, they're discovered from the members of the
+    /// Cargo workspace and the npm workspaces of the repository.
+    pub packages: Vec<Package>,
+}
+
+impl Default for MonorepoConfig {
+    fn default() -> Self {
+        MonorepoConfig {
+            enabled: false,
+            tag_format: "${name}@${version}".to_string(),
+            packages: vec![],
+        }
+    }
+}
+
+/// A package released on its own, from the commits changing its directory.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct Package {
+    pub name: String,
+    /// The directory of the package, relative to the root of the repository.
+    pub path: PathBuf,
+    /// The names of the packages of the repository this one depends on.
+    #[serde(default)]
+    pub dependencies: Vec<String>,
+}
+
+/// A dependency of a package released along with it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DependencyUpdate {
+    pub name: String,
+    pub version: String,
+}
+
+impl Package {
+    /// The tag format of the package releases, from the `tag_format` of the monorepo.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use semantic_release::packages::Package;
+    /// let package = Package {
+    ///     name: "semantic-release".to_string(),
+    ///     path: "crates/core".into(),
+    ///     dependencies: vec![],
+    /// };
+    ///
+    /// assert_eq!(package.tag_format("${name}@${version}"), "semantic-release@${version}");
+    /// ```
+    pub fn tag_format(&self, tag_format: &str) -> String {
+        tag_format.replace("${name}", &self.name)
+    }
+}
+
+/// Get the packages of the repository at `cwd`: the configured ones if any, else the
+/// members of its Cargo workspace and of its npm workspaces.
+pub fn get_packages(
+    cwd: &Path,
+    config: &MonorepoConfig,
+) -> Result<Vec<Package>, SemanticReleaseError> {
+    if !config.packages.is_empty() {
+        return Ok(config.packages.clone());
+    }
+
+    let mut packages = cargo_workspace_packages(cwd)?;
+
+    for package in npm_workspace_packages(cwd)? {
+        if !packages.iter().any(|found| found.path == package.path) {
+            packages.push(package);
+        }
+    }
+
+    // Only the dependencies on packages of the repository matter for their releases.
+    let names: Vec<String> = packages
+        .iter()
+        .map(|package| package.name.clone())
+        .collect();
+
+    for package in &mut packages {
+        let name = package.name.clone();
+
+        package
+            .dependencies
+            .retain(|dependency| *dependency != name && names.contains(dependency));
+        package.dependencies.sort();
+        package.dependencies.dedup();
+    }
+
+    Ok(packages)
+}
+
+/// Order `packages` so each one comes after the packages it depends on, keeping their
+/// order otherwise.
+///
+/// # Example
+///
+/// ```
+/// # use semantic_release::packages::{topological_order, Package};
+/// let package = |name: &str, dependencies: &[&str]| Package {
+///     name: name.to_string(),
+///     path: name.into(),
+///     dependencies: dependencies.iter().map(|name| name.to_string()).collect(),
+/// };
+/// let packages = [package("cli", &["core"]), package("core", &[])];
+///
+/// let order: Vec<&str> = topological_order(&packages)
+///     .unwrap()
+///     .iter()
+///     .map(|package| package.name.as_str())
+///     .collect();
+///
+/// assert_eq!(order, ["core", "cli"]);
+/// ```
+pub fn topological_order(packages: &[Package]) -> Result<Vec<&Package>, SemanticReleaseError> {
+    let mut ordered: Vec<&Package> = vec![];
+    let mut remaining: Vec<&Package> = packages.iter().collect();
+
+    while !remaining.is_empty() {
+        let is_ready = |package: &Package| {
+            package.dependencies.iter().all(|dependency| {
+                !remaining
+                    .iter()
+                    .any(|other| other.name == *dependency && other.name != package.name)
+            })
+        };
+
+        let Some(index) = remaining.iter().position(|package| is_ready(package)) else {
+            // Leave out the packages depending on the cycle without being part of it.
+            let mut cycle = remaining;
+
+            while let Some(index) = cycle.iter().position(|package| {
+                !cycle
+                    .iter()
+                    .any(|other| other.dependencies.contains(&package.name))
+            }) {
+                cycle.remove(index);
+            }
+
+            return Err(SemanticReleaseError::DependencyCycle {
+                packages: cycle.iter().map(|package| package.name.clone()).collect(),
+            });
+        };
+
+        ordered.push(remaining.remove(index));
+    }
+
+    Ok(ordered)
+}
+
+#[derive(Deserialize)]
+struct CargoManifest {
+    package: Option<CargoPackage>,
+    workspace: Option<CargoWorkspace>,
+}
+
+#[derive(Deserialize)]
+struct CargoPackage {
+    name: String,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default)]
+struct CargoWorkspace {
+    members: Vec<String>,
+    exclude: Vec<String>,
+}
+
+/// The packages of the Cargo workspace at `cwd`, including the root package if there's one.
+fn cargo_workspace_packages(cwd: &Path) -> Result<Vec<Package>, SemanticReleaseError> {
+    let Some(manifest) = read_manifest::<CargoManifest>(&cwd.join("Cargo.toml"))? else {
+        return Ok(vec![]);
+    };
+
+    let Some(workspace) = &manifest.workspace else {
+        return Ok(vec![]);
+    };
+
+    let mut packages: Vec<Package> = vec![];
+
+    if let Some(package) = &manifest.package {
+        packages.push(Package {
+            name: package.name.clone(),
+            path: PathBuf::new(),
+            dependencies: cargo_dependency_names(&cwd.join("Cargo.toml"))?,
+        });
+    }
+
+    for path in expand_members(cwd, &workspace.members, &workspace.exclude) {
+        let manifest_path = cwd.join(&path).join("Cargo.toml");
+
+        let Some(member) = read_manifest::<CargoManifest>(&manifest_path)? else {
+            continue;
+        };
+
+        if let Some(package) = &member.package {
+            packages.push(Package {
+                name: package.name.clone(),
+                path,
+                dependencies: cargo_dependency_names(&manifest_path)?,
+            });
+        }
+    }
+
+    Ok(packages)
+}
+
+/// The packages of the npm workspaces at `cwd`, listed either as an array or in the
+/// `packages` of an object.
+fn npm_workspace_packages(cwd: &Path) -> Result<Vec<Package>, SemanticReleaseError> {
+    let Some(manifest) = read_manifest::<Value>(&cwd.join("package.json"))? else {
+        return Ok(vec![]);
+    };
+
+    let workspaces = match &manifest["workspaces"] {
+        Value::Object(workspaces) => &workspaces["packages"],
+        workspaces => workspaces,
+    };
+
+    let patterns: Vec<String> = serde_json::from_value(workspaces.clone()).unwrap_or_default();
+
+    let mut packages = vec![];
+
+    for path in expand_members(cwd, &patterns, &[]) {
+        let manifest = read_manifest::<Value>(&cwd.join(&path).join("package.json"))?;
+
+        let Some(manifest) = manifest else {
+            continue;
+        };
+
+        if let Some(name) = manifest["name"].as_str() {
+            let dependencies = NPM_DEPENDENCY_KEYS
+                .iter()
+                .filter_map(|key| manifest[key].as_object())
+                .flat_map(|dependencies| dependencies.keys().cloned())
+                .collect();
+
+            packages.push(Package {
+                name: name.to_string(),
+                path,
+                dependencies,
+            });
+        }
+    }
+
+    Ok(packages)
+}
+
+/// Update the requirements on the given dependencies in the contents of a manifest.
+type UpdateRequirements = fn(&str, &[DependencyUpdate]) -> Result<String, String>;
+
+/// The tables of a Cargo manifest listing the dependencies its dependents build with it,
+/// which cascade releases. Dev-dependencies are left out, they would make cycles of
+/// packages testing each other.
+const CARGO_DEPENDENCY_TABLES: [&str; 2] = ["dependencies", "build-dependencies"];
+
+/// The keys of an npm manifest listing the dependencies that cascade releases.
+const NPM_DEPENDENCY_KEYS: [&str; 3] = ["dependencies", "optionalDependencies", "peerDependencies"];
+
+/// Update the requirements of `package` on its `updated_dependencies` to their new
+/// version, in its `Cargo.toml` and `package.json`, and return the manifests changed.
+///
+/// The operator of each requirement is kept, e.g. `^1.0.0` becomes `^1.1.0`. The
+/// requirements of dependencies declared with `workspace = true` are updated in the
+/// `[workspace.dependencies]` table of the root `Cargo.toml`. Path-only dependencies
+/// and ranges such as `>=1, <2` or `workspace:*` are left as is.
+pub fn update_dependency_requirements(
+    cwd: &Path,
+    package: &Package,
+    updated_dependencies: &[DependencyUpdate],
+) -> Result<Vec<PathBuf>, SemanticReleaseError> {
+    let dir = cwd.join(&package.path);
+    let mut updated = vec![];
+
+    if updated_dependencies.is_empty() {
+        return Ok(updated);
+    }
+
+    let mut manifests: Vec<(PathBuf, UpdateRequirements)> = vec![
+        (dir.join("Cargo.toml"), update_cargo_requirements),
+        (dir.join("package.json"), update_npm_requirements),
+    ];
+
+    let root = cwd.join("Cargo.toml");
+
+    if dir != cwd && inherits_requirements(&dir.join("Cargo.toml"), updated_dependencies) {
+        manifests.push((root, update_cargo_requirements));
+    }
+
+    for (path, update) in manifests {
+        let prepare_failed = |reason: String| SemanticReleaseError::PublishFailed {
+            step: "prepare".to_string(),
+            reason: format!("{}: {}", path.display(), reason),
+        };
+
+        let Ok(contents) = fs::read_to_string(&path) else {
+            continue;
+        };
+
+        let new_contents = update(&contents, updated_dependencies).map_err(prepare_failed)?;
+
+        if new_contents != contents {
+            fs::write(&path, new_contents).map_err(|e| prepare_failed(e.to_string()))?;
+
+            updated.push(path);
+        }
+    }
+
+    Ok(updated)
+}
+
+/// The dependency tables of a Cargo manifest, along with the ones of its
+/// `[target.'cfg(...)']` tables.
+fn cargo_dependency_tables(manifest: &mut DocumentMut) -> Vec<&mut dyn TableLike> {
+    let mut tables: Vec<&mut dyn TableLike> = vec![];
+
+    for (key, item) in manifest.iter_mut() {
+        if CARGO_DEPENDENCY_TABLES.contains(&key.get()) {
+            tables.extend(item.as_table_like_mut());
+        } else if key.get() == "target" {
+            let targets = item.as_table_like_mut().into_iter().flat_map(|targets| {
+                targets
+                    .iter_mut()
+                    .filter_map(|(_, target)| target.as_table_like_mut())
+            });
+
+            for target in targets {
+                for (key, item) in target.iter_mut() {
+                    if CARGO_DEPENDENCY_TABLES.contains(&key.get()) {
+                        tables.extend(item.as_table_like_mut());
+                    }
+                }
+            }
+        }
+    }
+
+    tables
+}
+
+/// The name of the package a dependency is on, which differs from its key when it's renamed.
+fn cargo_dependency_name(key: &str, dependency: &Item) -> String {
+    dependency
+        .get("package")
+        .and_then(|package| package.as_str())
+        .unwrap_or(key)
+        .to_string()
+}
+
+/// Parse the Cargo manifest at `path`, keeping its formatting.
+fn read_cargo_document(path: &Path) -> Result<DocumentMut, SemanticReleaseError> {
+    let contents = fs::read_to_string(path).unwrap_or_default();
+
+    contents
+        .parse()
+        .map_err(|e: TomlError| SemanticReleaseError::InvalidConfig {
+            path: path.to_path_buf(),
+            line: None,
+            column: None,
+            message: e.to_string(),
+        })
+}
+
+/// The names of the packages the Cargo manifest at `path` depends on, from the tables
+/// [`update_dependency_requirements`] updates.
+fn cargo_dependency_names(path: &Path) -> Result<Vec<String>, SemanticReleaseError> {
+    let mut manifest = read_cargo_document(path)?;
+
+    Ok(cargo_dependency_tables(&mut manifest)
+        .into_iter()
+        .flat_map(|table| {
+            table
+                .iter()
+                .map(|(key, dependency)| cargo_dependency_name(key, dependency))
+                .collect::<Vec<String>>()
+        })
+        .collect())
+}
+
+/// Whether the Cargo manifest at `path` inherits the requirement on any of the
+/// `updated_dependencies` from its workspace, with `workspace = true`.
+fn inherits_requirements(path: &Path, updated_dependencies: &[DependencyUpdate]) -> bool {
+    let Ok(mut manifest) = read_cargo_document(path) else {
+        return false;
+    };
+
+    cargo_dependency_tables(&mut manifest)
+        .into_iter()
+        .flat_map(|table| {
+            table
+                .iter()
+                .filter(|(_, dependency)| {
+                    dependency
+                        .get("workspace")
+                        .and_then(|workspace| workspace.as_bool())
+                        .unwrap_or(false)
+                })
+                .map(|(key, _)| key.to_string())
+                .collect::<Vec<String>>()
+        })
+        .any(|key| updated_dependencies.iter().any(|update| update.name == key))
+}
+
+/// Get `requirement` for `version`, keeping its operator, if it is a single requirement
+/// on a version like `^1.2` or `=1.2.3`.
+fn update_requirement(requirement: &str, version: &str) -> Option<String> {
+    let version_start = requirement.find(|c: char| c.is_ascii_digit())?;
+    let (operator, current) = requirement.split_at(version_start);
+
+    let single = operator
+        .chars()
+        .all(|c| matches!(c, '^' | '~' | '=' | '>' | '<'))
+        && current.chars().all(|c| c.is_ascii_digit() || c == '.');
+
+    single.then(|| format!("{}{}", operator, version))
+}
+
+fn update_cargo_requirements(
+    contents: &str,
+    updated_dependencies: &[DependencyUpdate],
+) -> Result<String, String> {
+    let mut manifest: DocumentMut = contents.parse().map_err(|e: TomlError| e.to_string())?;
+
+    for table in cargo_dependency_tables(&mut manifest) {
+        update_cargo_table(table, updated_dependencies);
+    }
+
+    // The requirements the members inherit with `workspace = true`.
+    let workspace_dependencies = manifest
+        .get_mut("workspace")
+        .and_then(|workspace| workspace.get_mut("dependencies"))
+        .and_then(|table| table.as_table_like_mut());
+
+    if let Some(table) = workspace_dependencies {
+        update_cargo_table(table, updated_dependencies);
+    }
+
+    Ok(manifest.to_string())
+}
+
+/// Update the requirements on the `updated_dependencies` in a dependency table.
+fn update_cargo_table(table: &mut dyn TableLike, updated_dependencies: &[DependencyUpdate]) {
+    for (key, dependency) in table.iter_mut() {
+        let name = cargo_dependency_name(key.get(), dependency);
+
+        let Some(update) = updated_dependencies
+            .iter()
+            .find(|update| update.name == name)
+        else {
+            continue;
+        };
+
+        let requirement = match dependency.as_table_like_mut() {
+            Some(dependency) => dependency.get_mut("version"),
+            None => Some(dependency),
+        };
+
+        let Some(requirement) = requirement.and_then(|item| item.as_value_mut()) else {
+            continue;
+        };
+
+        let Some(new_requirement) = requirement
+            .as_str()
+            .and_then(|requirement| update_requirement(requirement, &update.version))
+        else {
+            continue;
+        };
+
+        let decor = requirement.decor().clone();
+
+        *requirement = new_requirement.into();
+        *requirement.decor_mut() = decor;
+    }
+}
+
+/// Update the requirements in the text of the manifest, so its formatting is kept.
+fn update_npm_requirements(
+    contents: &str,
+    updated_dependencies: &[DependencyUpdate],
+) -> Result<String, String> {
+    let manifest: Value = serde_json::from_str(contents).map_err(|e| e.to_string())?;
+    let mut contents = contents.to_string();
+
+    for update in updated_dependencies {
+        let listed = NPM_DEPENDENCY_KEYS
+            .iter()
+            .any(|key| manifest[key].get(&update.name).is_some());
+
+        if !listed {
+            continue;
+        }
+
+        let re = Regex::new(&format!(
+            r#"("{}"\s*:\s*")([^"]*)(")"#,
+            regex::escape(&update.name)
+        ))
+        .expect("should be a valid regex");
+
+        contents = re
+            .replace_all(&contents, |caps: &Captures| {
+                match update_requirement(&caps[2], &update.version) {
+                    Some(requirement) => format!("{}{}{}", &caps[1], requirement, &caps[3]),
+                    None => caps[0].to_string(),
+                }
+            })
+            .into_owned();
+    }
+
+    Ok(contents)
+}
+
+/// Read the manifest at `path`, if it exists.
+fn read_manifest<T: serde::de::DeserializeOwned>(
+    path: &Path,
+) -> Result<Option<T>, SemanticReleaseError> {
+    let Ok(contents) = fs::read_to_string(path) else {
+        return Ok(None);
+    };
+
+    let format = ConfigFormat::detect(path, &contents);
+
+    parse(path, &contents, format).map(Some)
+}
+
+/// Expand the workspace member `patterns`, globs like `crates/*`, to the directories
+/// they match under `cwd`, skipping the `exclude`d ones.
+fn expand_members(cwd: &Path, patterns: &[String], exclude: &[String]) -> Vec<PathBuf> {
+    let mut members: Vec<PathBuf> = vec![];
+
+    for pattern in patterns {
+        let mut paths = vec![PathBuf::new()];
+
+        for component in pattern.trim_start_matches("./").split('/') {
+            if component.is_empty() || component == "." {
+                continue;
+            }
+
+            paths = paths
+                .into_iter()
+                .flat_map(|path| {
+                    if !branches::is_glob(component) {
+                        return vec![path.join(component)];
+                    }
+
+                    let mut entries: Vec<PathBuf> = fs::read_dir(cwd.join(&path))
+                        .into_iter()
+                        .flatten()
+                        .flatten()
+                        .filter(|entry| entry.path().is_dir())
+                        .filter_map(|entry| entry.file_name().into_string().ok())
+                        .filter(|name| !name.starts_with('.'))
+                        .filter(|name| branches::matches(component, name))
+                        .map(|name| path.join(name))
+                        .collect();
+
+                    entries.sort();
+                    entries
+                })
+                .collect();
+        }
+
+        for path in paths {
+            let excluded = exclude
+                .iter()
+                .any(|exclude| Path::new(exclude.trim_start_matches("./")) == path);
+
+            if cwd.join(&path).is_dir() && !excluded && !members.contains(&path) {
+                members.push(path);
+            }
+        }
+    }
+
+    members
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_workspace_packages() {
+        let dir =
+            std::env::temp_dir().join(format!("semantic-release-packages-{}", std::process::id()));
+
+        for (path, contents) in [
+            (
+                "Cargo.toml",
+                "[workspace]\nmembers = [\"crates/*\"]\nexclude = [\"crates/internal\"]\n",
+            ),
+            ("crates/core/Cargo.toml", "[package]\nname = \"core\"\n"),
+            (
+                "crates/cli/Cargo.toml",
+                "[package]\nname = \"cli\"\n\n[dependencies]\n\
+                 semantic-core = { package = \"core\", path = \"../core\" }\n\
+                 serde = \"1\"\n",
+            ),
+            (
+                "crates/internal/Cargo.toml",
+                "[package]\nname = \"internal\"\n",
+            ),
+            (
+                "crates/os/Cargo.toml",
+                "[package]\nname = \"os\"\n\n\
+                 [target.'cfg(unix)'.dependencies]\n\
+                 core = { workspace = true }\n\n\
+                 [dev-dependencies]\n\
+                 cli = { path = \"../cli\" }\n",
+            ),
+            (
+                "package.json",
+                r#"{ "workspaces": { "packages": ["packages/*"] } }"#,
+            ),
+            (
+                "packages/web/package.json",
+                r#"{ "name": "@acme/web", "dependencies": { "@acme/ui": "^1.0.0" } }"#,
+            ),
+            ("packages/ui/package.json", r#"{ "name": "@acme/ui" }"#),
+        ] {
+            let path = dir.join(path);
+
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, contents).unwrap();
+        }
+
+        let packages = get_packages(&dir, &MonorepoConfig::default()).unwrap();
+
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(
+            packages,
+            vec![
+                Package {
+                    name: "cli".to_string(),
+                    path: "crates/cli".into(),
+                    dependencies: vec!["core".to_string()],
+                },
+                Package {
+                    name: "core".to_string(),
+                    path: "crates/core".into(),
+                    dependencies: vec![],
+                },
+                Package {
+                    name: "os".to_string(),
+                    path: "crates/os".into(),
+                    dependencies: vec!["core".to_string()],
+                },
+                Package {
+                    name: "@acme/ui".to_string(),
+                    path: "packages/ui".into(),
+                    dependencies: vec![],
+                },
+                Package {
+                    name: "@acme/web".to_string(),
+                    path: "packages/web".into(),
+                    dependencies: vec!["@acme/ui".to_string()],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_update_dependency_requirements() {
+        let dir = std::env::temp_dir().join(format!(
+            "semantic-release-requirements-{}",
+            std::process::id()
+        ));
+        fs::create_dir_all(&dir).unwrap();
+
+        fs::write(
+            dir.join("Cargo.toml"),
+            "[package]\nname = \"app\"\n\n\
+             [dependencies]\n\
+             core = { path = \"../core\", version = \"1.0\" } # pinned\n\
+             semantic-cli = { package = \"cli\", version = \"=1.0.0\" }\n\
+             serde = \"1\"\n\n\
+             [target.'cfg(unix)'.dependencies]\n\
+             ui = \"^1.0.0\"\n\n\
+             [dev-dependencies.core]\n\
+             path = \"../core\"\n",
+        )
+        .unwrap();
+        fs::write(
+            dir.join("package.json"),
+            "{\n  \"name\": \"app\",\n  \"dependencies\": {\n    \"core\": \"~1.0.0\",\n    \"ui\": \"workspace:*\"\n  }\n}\n",
+        )
+        .unwrap();
+
+        let package = Package {
+            name: "app".to_string(),
+            path: PathBuf::new(),
+            dependencies: vec!["cli".to_string(), "core".to_string(), "ui".to_string()],
+        };
+        let updates = ["cli", "core", "ui"].map(|name| DependencyUpdate {
+            name: name.to_string(),
+            version: "1.1.0".to_string(),
+        });
+
+        let updated = update_dependency_requirements(&dir, &package, &updates).unwrap();
+
+        let cargo = fs::read_to_string(dir.join("Cargo.toml")).unwrap();
+        let npm = fs::read_to_string(dir.join("package.json")).unwrap();
+
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(updated.len(), 2);
+        assert_eq!(
+            cargo,
+            "[package]\nname = \"app\"\n\n\
+             [dependencies]\n\
+             core = { path = \"../core\", version = \"1.1.0\" } # pinned\n\
+             semantic-cli = { package = \"cli\", version = \"=1.1.0\" }\n\
+             serde = \"1\"\n\n\
+             [target.'cfg(unix)'.dependencies]\n\
+             ui = \"^1.1.0\"\n\n\
+             [dev-dependencies.core]\n\
+             path = \"../core\"\n"
+        );
+        assert_eq!(
+            npm,
+            "{\n  \"name\": \"app\",\n  \"dependencies\": {\n    \"core\": \"~1.1.0\",\n    \"ui\": \"workspace:*\"\n  }\n}\n"
+        );
+    }
+
+    #[test]
+    fn test_update_workspace_requirements() {
+        let dir = std::env::temp_dir().join(format!(
+            "semantic-release-workspace-requirements-{}",
+            std::process::id()
+        ));
+        fs::create_dir_all(dir.join("crates/app")).unwrap();
+
+        fs::write(
+            dir.join("Cargo.toml"),
+            "[workspace]\nmembers = [\"crates/*\"]\n\n\
+             [workspace.dependencies]\n\
+             core = { path = \"crates/core\", version = \"1.0.0\" }\n\
+             serde = \"1\"\n",
+        )
+        .unwrap();
+        fs::write(
+            dir.join("crates/app/Cargo.toml"),
+            "[package]\nname = \"app\"\n\n\
+             [target.'cfg(unix)'.dependencies]\n\
+             core = { workspace = true }\n",
+        )
+        .unwrap();
+
+        let package = Package {
+            name: "app".to_string(),
+            path: "crates/app".into(),
+            dependencies: vec!["core".to_string()],
+        };
+        let updates = [DependencyUpdate {
+            name: "core".to_string(),
+            version: "1.1.0".to_string(),
+        }];
+
+        let updated = update_dependency_requirements(&dir, &package, &updates).unwrap();
+
+        let root = fs::read_to_string(dir.join("Cargo.toml")).unwrap();
+        let member = fs::read_to_string(dir.join("crates/app/Cargo.toml")).unwrap();
+
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(updated, vec![dir.join("Cargo.toml")]);
+        assert!(root.contains("core = { path = \"crates/core\", version = \"1.1.0\" }\n"));
+        assert!(member.contains("core = { workspace = true }\n"));
+    }
+
+    #[test]
+    fn test_topological_order() {
+        let package = |name: &str, dependencies: &[&str]| Package {
+            name: name.to_string(),
+            path: name.into(),
+            dependencies: dependencies.iter().map(|name| name.to_string()).collect(),
+        };
+
+        let packages = [
+            package("app", &["cli", "core"]),
+            package("cli", &["core"]),
+            package("docs", &[]),
+            package("core", &[]),
+        ];
+
+        let order: Vec<&str> = topological_order(&packages)
+            .unwrap()
+            .iter()
+            .map(|package| package.name.as_str())
+            .collect();
+
+        assert_eq!(order, ["docs", "core", "cli", "app"]);
+
+        let packages = [
+            package("app", &["cli"]),
+            package("cli", &["core"]),
+            package("core", &["cli"]),
+        ];
+
+        match topological_order(&packages) {
+            Err(SemanticReleaseError::DependencyCycle { packages }) => {
+                assert_eq!(packages, ["cli", "core"])
+            }
+            result => panic!("Expected a dependency cycle, got {:?}", result),
+        }
+    }
+}
@@ -0,0 +1,222 @@
+use std::{
+    io::{self, Read, Write},
+    path::Path,
+    process::{Command, Stdio},
+    thread,
+};
+
+use serde_json::Value;
+
+use crate::{
+    error::SemanticReleaseError,
+    logger::{Level, Logger},
+};
+
+/// The steps of a release plugins take part in, in the order they run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Step {
+    VerifyConditions,
+    AnalyzeCommits,
+    VerifyRelease,
+    GenerateNotes,
+    Prepare,
+    Publish,
+    Success,
+}
+
+impl Step {
+    /// The name of the step, given to the plugins as their last argument.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Step::VerifyConditions => "verify_conditions",
+            Step::AnalyzeCommits => "analyze_commits",
+            Step::VerifyRelease => "verify_release",
+            Step::GenerateNotes => "generate_notes",
+            Step::Prepare => "prepare",
+            Step::Publish => "publish",
+            Step::Success => "success",
+        }
+    }
+
+    /// Whether what the plugins print is the result of the step rather than logs.
+    fn has_output(&self) -> bool {
+        matches!(self, Step::AnalyzeCommits | Step::GenerateNotes)
+    }
+}
+
+/// Run a release step for every plugin and return what they printed, in order.
+///
+/// A plugin is a command, optionally followed by arguments, e.g. `./scripts/publish.sh --dry`.
+/// It's run from `cwd` with the name of the step as last argument and the release
+/// context as JSON on stdin, and fails the step by exiting with a non-zero status.
+/// Plugins ignore the steps they don't implement by exiting successfully without output.
+///
+/// What plugins print on stderr is logged as it arrives with the plugin as prefix, as is
+/// what they print on stdout for the steps that don't return anything.
+pub fn run_step(
+    plugins: &[String],
+    step: Step,
+    cwd: &Path,
+    input: &Value,
+    logger: &Logger,
+) -> Result<Vec<String>, SemanticReleaseError> {
+    let mut outputs = vec![];
+
+    for plugin in plugins {
+        let plugin_failed = |reason: String| SemanticReleaseError::PluginFailed {
+            plugin: plugin.clone(),
+            step: step.name().to_string(),
+            reason,
+        };
+
+        let mut args = plugin.split_whitespace();
+        let program = match args.next() {
+            Some(program) => program,
+            None => continue,
+        };
+
+        let plugin_logger = logger.scope(plugin);
+
+        plugin_logger.debug(format!("Running the {} step", step.name()));
+
+        let mut child = Command::new(program)
+            .args(args)
+            .arg(step.name())
+            .current_dir(cwd)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| plugin_failed(e.to_string()))?;
+
+        let input = input.to_string();
+        let stdin = child.stdin.take();
+        let stdout = child.stdout.take();
+
+        // Feed stdin and read stdout on their own threads while stderr is logged as it
+        // arrives, so a plugin filling one pipe never blocks on another.
+        let writer = thread::spawn(move || {
+            if let Some(mut stdin) = stdin {
+                // Plugins are free not to read their input.
+                let _ = stdin.write_all(input.as_bytes());
+            }
+        });
+        let reader = thread::spawn(move || {
+            let mut output = Vec::new();
+
+            if let Some(mut stdout) = stdout {
+                let _ = stdout.read_to_end(&mut output);
+            }
+
+            output
+        });
+
+        if let Some(mut stderr) = child.stderr.take() {
+            let mut log = plugin_logger.writer(Level::Info);
+
+            io::copy(&mut stderr, &mut log)
+                .and_then(|_| log.flush())
+                .map_err(|e| plugin_failed(e.to_string()))?;
+        }
+
+        let _ = writer.join();
+        let output = reader.join().unwrap_or_default();
+
+        let status = child.wait().map_err(|e| plugin_failed(e.to_string()))?;
+
+        if !status.success() {
+            return Err(plugin_failed(status.to_string()));
+        }
+
+        let stdout = String::from_utf8_lossy(&output).trim().to_string();
+
+        if stdout.is_empty() {
+            continue;
+        }
+
+        if step.has_output() {
+            outputs.push(stdout);
+        } else {
+            plugin_logger.info(stdout);
+        }
+    }
+
+    Ok(outputs)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, fs, rc::Rc};
+
+    use serde_json::json;
+
+    use super::*;
+
+    /// A writer whose content can be read after being moved into a logger.
+    #[derive(Clone, Default)]
+    struct Buffer(Rc<RefCell<Vec<u8>>>);
+
+    impl Write for Buffer {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.borrow_mut().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_run_step_output() {
+        let dir =
+            std::env::temp_dir().join(format!("semantic-release-plugins-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(
+            dir.join("plugin.sh"),
+            "read input\necho \"$input\" >&2\necho \"$1 done\"\n",
+        )
+        .unwrap();
+
+        let buffer = Buffer::default();
+        let logger = Logger::new(buffer.clone(), false, false, str::to_string);
+        let plugins = vec!["sh plugin.sh".to_string(), "true".to_string()];
+        let input = json!({ "dry_run": true });
+
+        let notes = run_step(&plugins, Step::GenerateNotes, &dir, &input, &logger).unwrap();
+        let logs = run_step(&plugins, Step::Prepare, &dir, &input, &logger).unwrap();
+
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(notes, ["generate_notes done"]);
+        assert!(logs.is_empty());
+        assert_eq!(
+            String::from_utf8(buffer.0.borrow().clone()).unwrap(),
+            "[semantic-release] [sh plugin.sh] › ℹ  {\"dry_run\":true}\n\
+             [semantic-release] [sh plugin.sh] › ℹ  {\"dry_run\":true}\n\
+             [semantic-release] [sh plugin.sh] › ℹ  prepare done\n"
+        );
+    }
+
+    #[test]
+    fn test_run_step_failure() {
+        let logger = Logger::new(io::sink(), false, false, str::to_string);
+        let plugins = vec![
+            "false".to_string(),
+            "semantic-release-missing-plugin".to_string(),
+        ];
+        let run = |plugins: &[String]| {
+            run_step(plugins, Step::Publish, Path::new("."), &json!({}), &logger).unwrap_err()
+        };
+
+        for error in [run(&plugins[..1]), run(&plugins[1..])] {
+            assert_eq!(error.code(), "EPLUGINFAILED");
+        }
+
+        match run(&plugins) {
+            SemanticReleaseError::PluginFailed { plugin, step, .. } => {
+                assert_eq!((plugin.as_str(), step.as_str()), ("false", "publish"))
+            }
+            error => panic!("unexpected error {}", error),
+        }
+    }
+}
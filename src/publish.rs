@@ -0,0 +1,325 @@
+use crate::{
+    context::Context,
+    error::SemanticReleaseError,
+    git::GitRepo,
+    packages::update_dependency_requirements,
+    plugins::{run_step, Step},
+    release::{plugin_input, NextRelease, ReleasePlan},
+};
+
+/// Publish a planned release: update the requirements of a package on its updated
+/// dependencies, run the `prepare` plugins, create and push the tag, then run the
+/// `publish` and `success` plugins.
+///
+/// The tag points to HEAD once the `prepare` plugins ran, so it includes the commits they
+/// make. The updated manifests they leave uncommitted are committed and pushed to the
+/// branch first. The tag is deleted locally when it can't be pushed, so the next run
+/// creates it again.
+pub fn publish_release(
+    context: &Context,
+    repo: &GitRepo,
+    plan: &ReleasePlan,
+) -> Result<(), SemanticReleaseError> {
+    let Some(next_release) = &plan.next_release else {
+        return Ok(());
+    };
+
+    let config = &context.config;
+    let package = plan.package.as_ref();
+    let logger = match package {
+        Some(package) => context.logger.scope(&package.name),
+        None => context.logger.clone(),
+    };
+    let input = |step, next_release| {
+        plugin_input(
+            context,
+            step,
+            package,
+            plan.last_release.as_ref(),
+            &plan.commits,
+            Some(next_release),
+        )
+    };
+
+    let mut updated = vec![];
+
+    if let Some(package) = package {
+        updated = update_dependency_requirements(
+            &context.cwd,
+            package,
+            &next_release.updated_dependencies,
+        )?;
+
+        for path in &updated {
+            logger.info(format!(
+                "Updated the requirements on the released dependencies in {}",
+                path.display()
+            ));
+        }
+    }
+
+    run_step(
+        &config.plugins,
+        Step::Prepare,
+        &context.cwd,
+        &input(Step::Prepare, next_release),
+        &logger,
+    )?;
+
+    let publish_failed = |step: &str, reason: String| SemanticReleaseError::PublishFailed {
+        step: step.to_string(),
+        reason,
+    };
+
+    let message = format!("chore(release): {} [skip ci]", next_release.git_tag);
+
+    if let Some(oid) = repo
+        .commit_files(&updated, &message)
+        .map_err(|e| publish_failed("commit", e.message().to_string()))?
+    {
+        repo.push_branch(&config.repository_url, &context.branch, &config.credentials)
+            .map_err(|e| publish_failed("commit", e.to_string()))?;
+
+        logger.info(format!(
+            "Committed the updated requirements in {} and pushed it to the branch {}",
+            oid, context.branch
+        ));
+    }
+
+    let head = repo
+        .get_git_head()
+        .map_err(|e| publish_failed("tag", e.message().to_string()))?;
+
+    let next_release = &NextRelease {
+        git_head: head.to_string(),
+        ..next_release.clone()
+    };
+
+    repo.create_tag(&next_release.git_tag, head)
+        .map_err(|e| publish_failed("tag", e.message().to_string()))?;
+
+    if let Err(error) = repo.push_tag(
+        &config.repository_url,
+        &next_release.git_tag,
+        &config.credentials,
+    ) {
+        if let Err(e) = repo.delete_tag(&next_release.git_tag) {
+            logger.warn(format!(
+                "Couldn't delete the tag {} that wasn't pushed: {}",
+                next_release.git_tag,
+                e.message()
+            ));
+        }
+
+        return Err(publish_failed("tag", error.to_string()));
+    }
+
+    run_step(
+        &config.plugins,
+        Step::Publish,
+        &context.cwd,
+        &input(Step::Publish, next_release),
+        &logger,
+    )?;
+    run_step(
+        &config.plugins,
+        Step::Success,
+        &context.cwd,
+        &input(Step::Success, next_release),
+        &logger,
+    )?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use std::{io, path::Path};
+
+    use git2::{Repository, RepositoryInitOptions, Signature, StatusOptions};
+
+    use super::*;
+    use crate::{
+        ci::CiEnv,
+        get_config::Config,
+        logger::Logger,
+        packages::{get_packages, MonorepoConfig},
+        release::{plan_package_releases, plan_release, tests::release_repo},
+    };
+
+    #[test]
+    fn test_publish_release() {
+        let dir =
+            std::env::temp_dir().join(format!("semantic-release-publish-{}", std::process::id()));
+        let (repo, mut context) = release_repo(&dir.join("repo"));
+        let remote = Repository::init_bare(dir.join("remote.git")).unwrap();
+        context.config.repository_url = format!("file://{}", dir.join("remote.git").display());
+
+        let plan = plan_release(&context, &repo, None, &[], &[]).unwrap();
+        publish_release(&context, &repo, &plan).unwrap();
+
+        let steps = fs::read_to_string(dir.join("repo/steps.txt")).unwrap();
+        let pushed = remote.find_reference("refs/tags/v1.1.0").is_ok();
+
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert!(steps.ends_with("generate_notes\nprepare\npublish\nsuccess\n"));
+        assert!(pushed);
+    }
+
+    #[test]
+    fn test_push_failure_deletes_tag() {
+        let dir = std::env::temp_dir().join(format!(
+            "semantic-release-publish-failure-{}",
+            std::process::id()
+        ));
+        let (repo, mut context) = release_repo(&dir.join("repo"));
+        context.config.repository_url = format!("file://{}", dir.join("missing.git").display());
+
+        let plan = plan_release(&context, &repo, None, &[], &[]).unwrap();
+        let error = publish_release(&context, &repo, &plan).unwrap_err();
+
+        let steps = fs::read_to_string(dir.join("repo/steps.txt")).unwrap();
+        let tagged = repo.repository().find_reference("refs/tags/v1.1.0").is_ok();
+
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(error.code(), "EPUBLISH");
+        assert!(steps.ends_with("generate_notes\nprepare\n"));
+        assert!(!tagged);
+    }
+
+    #[test]
+    fn test_publish_commits_updated_requirements() {
+        let dir = std::env::temp_dir().join(format!(
+            "semantic-release-publish-requirements-{}",
+            std::process::id()
+        ));
+        let path = dir.join("repo");
+        let remote = Repository::init_bare(dir.join("remote.git")).unwrap();
+        let git = Repository::init_opts(&path, RepositoryInitOptions::new().initial_head("main"))
+            .unwrap();
+        git.config().unwrap().set_str("user.name", "Jane").unwrap();
+        git.config()
+            .unwrap()
+            .set_str("user.email", "jane@example.com")
+            .unwrap();
+
+        let commit = |message: &str, files: &[(&str, &str)]| {
+            let mut index = git.index().unwrap();
+
+            for (file, contents) in files {
+                fs::create_dir_all(path.join(file).parent().unwrap()).unwrap();
+                fs::write(path.join(file), contents).unwrap();
+                index.add_path(Path::new(file)).unwrap();
+            }
+
+            index.write().unwrap();
+
+            let tree = git.find_tree(index.write_tree().unwrap()).unwrap();
+            let signature = Signature::now("Jane", "jane@example.com").unwrap();
+            let parents: Vec<git2::Commit> = git
+                .head()
+                .ok()
+                .map(|head| head.peel_to_commit().unwrap())
+                .into_iter()
+                .collect();
+
+            git.commit(
+                Some("HEAD"),
+                &signature,
+                &signature,
+                message,
+                &tree,
+                &parents.iter().collect::<Vec<&git2::Commit>>(),
+            )
+            .unwrap()
+        };
+
+        let initial = commit(
+            "feat: initial",
+            &[
+                ("Cargo.toml", "[workspace]\nmembers = [\"crates/*\"]\n"),
+                (
+                    "crates/core/Cargo.toml",
+                    "[package]\nname = \"core\"\nversion = \"1.0.0\"\n",
+                ),
+                (
+                    "crates/app/Cargo.toml",
+                    "[package]\nname = \"app\"\nversion = \"1.0.0\"\n\n\
+                     [dependencies]\ncore = { path = \"../core\", version = \"1.0.0\" }\n",
+                ),
+            ],
+        );
+
+        for tag in ["core@1.0.0", "app@1.0.0"] {
+            git.tag_lightweight(tag, &git.find_object(initial, None).unwrap(), false)
+                .unwrap();
+        }
+
+        commit("feat: parse input", &[("crates/core/src/lib.rs", "")]);
+
+        let monorepo = MonorepoConfig {
+            enabled: true,
+            ..MonorepoConfig::default()
+        };
+        let context = Context {
+            cwd: path.clone(),
+            is_ci: true,
+            is_pr: false,
+            branch: "main".to_string(),
+            config: Config {
+                repository_url: format!("file://{}", dir.join("remote.git").display()),
+                monorepo: monorepo.clone(),
+                ..Config::default()
+            },
+            ci_env: CiEnv::default(),
+            logger: Logger::new(io::sink(), false, false, str::to_string),
+        };
+        let repo = GitRepo::open(&path).unwrap();
+
+        let packages = get_packages(&path, &monorepo).unwrap();
+        let plans = plan_package_releases(&context, &repo, &packages).unwrap();
+
+        for plan in &plans {
+            publish_release(&context, &repo, plan).unwrap();
+        }
+
+        let app = plans
+            .iter()
+            .find_map(|plan| {
+                plan.package
+                    .as_ref()
+                    .filter(|package| package.name == "app")
+                    .and(plan.next_release.as_ref())
+            })
+            .unwrap();
+
+        let tagged = remote
+            .revparse_single(&format!("{}:crates/app/Cargo.toml", app.git_tag))
+            .unwrap();
+        let tagged = String::from_utf8(tagged.peel_to_blob().unwrap().content().to_vec()).unwrap();
+        let tag_commit = remote
+            .revparse_single(&app.git_tag)
+            .unwrap()
+            .peel_to_commit()
+            .unwrap();
+        let branch = remote.revparse_single("main").unwrap().id();
+        let dirty = git
+            .statuses(Some(StatusOptions::new().include_untracked(false)))
+            .unwrap()
+            .len();
+
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert!(tagged.contains("core = { path = \"../core\", version = \"1.1.0\" }"));
+        assert_eq!(tag_commit.id(), branch);
+        assert_eq!(
+            tag_commit.summary(),
+            Some(format!("chore(release): {} [skip ci]", app.git_tag).as_str())
+        );
+        assert_eq!(dirty, 0);
+    }
+}
@@ -0,0 +1,687 @@
+use std::{
+    io::{self, Write},
+    path::PathBuf,
+};
+
+use git2::Oid;
+use regex::Regex;
+use semver::Version;
+use serde::Serialize;
+use serde_json::{json, Value};
+
+use crate::{
+    analyze_commits::analyze_commits,
+    branches,
+    context::Context,
+    error::SemanticReleaseError,
+    generate_notes::{generate_notes, today, NotesContext},
+    get_config::ReleaseType,
+    git::{Commit, CommitQuery, GitRepo},
+    packages::{topological_order, DependencyUpdate, Package},
+    plugins::{run_step, Step},
+    report::render_markdown,
+};
+
+/// The last release of the branch, found in its tags.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct LastRelease {
+    pub version: String,
+    pub git_tag: String,
+    pub git_head: String,
+}
+
+/// The release triggered by the commits since the last release.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct NextRelease {
+    pub version: String,
+    pub git_tag: String,
+    pub git_head: String,
+    pub release_type: ReleaseType,
+    /// The distribution channel, `None` for the default one.
+    pub channel: Option<String>,
+    pub notes: String,
+    /// The packages of the repository this one depends on released along with it, in
+    /// monorepo mode. Their requirements are updated in the manifests before `prepare`.
+    pub updated_dependencies: Vec<DependencyUpdate>,
+}
+
+/// Everything a run found out about the release, before publishing anything.
+#[derive(Debug, Serialize)]
+pub struct ReleasePlan {
+    /// The package released, in monorepo mode.
+    pub package: Option<Package>,
+    /// `None` for the first release, when no tag matches the tag format.
+    pub last_release: Option<LastRelease>,
+    /// The commits since the last release, newest first.
+    pub commits: Vec<Commit>,
+    /// `None` when the commits don't trigger a release.
+    pub next_release: Option<NextRelease>,
+}
+
+/// Get the version of a tag created with `tag_format`, if it is one.
+///
+/// # Example
+///
+/// ```
+/// # use semantic_release::release::tag_version;
+/// assert_eq!(tag_version("v${version}", "v1.2.3").unwrap().to_string(), "1.2.3");
+/// assert_eq!(tag_version("v${version}", "1.2.3"), None);
+/// ```
+pub fn tag_version(tag_format: &str, tag: &str) -> Option<Version> {
+    let pattern = regex::escape(tag_format).replace(r"\$\{version\}", "(.+)");
+    let re = Regex::new(&format!("^{}$", pattern)).ok()?;
+
+    Version::parse(re.captures(tag)?.get(1)?.as_str()).ok()
+}
+
+/// Get the release with the highest version among the tags reachable from `head`.
+///
+/// Pre-release versions are ignored.
+pub fn get_last_release(
+    repo: &GitRepo,
+    tag_format: &str,
+    head: Oid,
+) -> Result<Option<LastRelease>, git2::Error> {
+    let last_release = repo
+        .get_reachable_tags(head)?
+        .into_iter()
+        .filter_map(|(tag, oid)| {
+            let version = tag_version(tag_format, &tag)?;
+
+            version.pre.is_empty().then_some((version, tag, oid))
+        })
+        .max_by(|(a, _, _), (b, _, _)| a.cmp(b))
+        .map(|(version, git_tag, oid)| LastRelease {
+            version: version.to_string(),
+            git_tag,
+            git_head: oid.to_string(),
+        });
+
+    Ok(last_release)
+}
+
+/// Get the version following `version` for a release of type `release_type`.
+///
+/// # Example
+///
+/// ```
+/// # use semantic_release::{get_config::ReleaseType, release::next_version};
+/// # use semver::Version;
+/// let version = Version::new(1, 2, 3);
+///
+/// assert_eq!(next_version(&version, ReleaseType::Minor), Some(Version::new(1, 3, 0)));
+/// assert_eq!(next_version(&version, ReleaseType::None), None);
+/// ```
+pub fn next_version(version: &Version, release_type: ReleaseType) -> Option<Version> {
+    match release_type {
+        ReleaseType::None => None,
+        ReleaseType::Patch => Some(Version::new(
+            version.major,
+            version.minor,
+            version.patch + 1,
+        )),
+        ReleaseType::Minor => Some(Version::new(version.major, version.minor + 1, 0)),
+        ReleaseType::Major => Some(Version::new(version.major + 1, 0, 0)),
+    }
+}
+
+/// Get the channel releases of the branch are distributed on.
+///
+/// The first release branch of the `branches` option that exists in the repository
+/// publishes on the default channel, and every other branch on the channel named after it.
+pub fn get_channel(context: &Context, repo: &GitRepo) -> Option<String> {
+    let first_release_branch = context
+        .config
+        .branches
+        .iter()
+        .find(|branch| !branches::is_glob(branch) && repo.branch_exists(branch));
+
+    match first_release_branch {
+        Some(branch) if *branch == context.branch => None,
+        _ => Some(context.branch.clone()),
+    }
+}
+
+/// Run the read-only steps of a release: find the last release, analyze the commits
+/// since, and compute the next version and its notes, running the plugins of each step.
+///
+/// Without a last release, every commit up to the root commits is analyzed and the
+/// next release is the first one, with the `initial_version`.
+///
+/// The release of a `package` only considers its tags and the commits changing its directory,
+/// outside of the `nested_paths` of the packages within it. It is at least a patch release
+/// when some of its `updated_dependencies` are released.
+pub fn plan_release(
+    context: &Context,
+    repo: &GitRepo,
+    package: Option<&Package>,
+    nested_paths: &[PathBuf],
+    updated_dependencies: &[DependencyUpdate],
+) -> Result<ReleasePlan, SemanticReleaseError> {
+    let git_error = |operation: &str| {
+        let operation = operation.to_string();
+
+        move |e: git2::Error| SemanticReleaseError::Git {
+            operation,
+            reason: e.message().to_string(),
+        }
+    };
+
+    let config = &context.config;
+
+    let logger = match package {
+        Some(package) => context.logger.scope(&package.name),
+        None => context.logger.clone(),
+    };
+    let tag_format = match package {
+        Some(package) => package.tag_format(&config.monorepo.tag_format),
+        None => config.tag_format.clone(),
+    };
+    let input = |step, last_release, commits, next_release| {
+        plugin_input(context, step, package, last_release, commits, next_release)
+    };
+
+    run_step(
+        &config.plugins,
+        Step::VerifyConditions,
+        &context.cwd,
+        &input(Step::VerifyConditions, None, &[], None),
+        &logger,
+    )?;
+
+    let head = repo.get_git_head().map_err(git_error("resolve HEAD"))?;
+
+    let last_release =
+        get_last_release(repo, &tag_format, head).map_err(git_error("read the tags"))?;
+
+    match &last_release {
+        Some(last_release) => logger.info(format!(
+            "Found git tag {} associated with version {} on branch {}",
+            last_release.git_tag, last_release.version, context.branch
+        )),
+        None => logger.info(format!(
+            "No git tag version found on branch {}",
+            context.branch
+        )),
+    }
+
+    let commits = repo
+        .query_commits(&CommitQuery {
+            to: Some(head),
+            since_tag: last_release
+                .as_ref()
+                .map(|last_release| last_release.git_tag.clone()),
+            paths: package
+                .map(|package| package.path.clone())
+                .filter(|path| !path.as_os_str().is_empty())
+                .into_iter()
+                .collect(),
+            exclude_paths: nested_paths.to_vec(),
+            exclude_merges: false,
+            files: false,
+        })
+        .map_err(git_error("read the commits since the last release"))?;
+
+    logger.info(format!(
+        "Found {} commits since last release",
+        commits.len()
+    ));
+
+    let analyzed = run_step(
+        &config.plugins,
+        Step::AnalyzeCommits,
+        &context.cwd,
+        &input(Step::AnalyzeCommits, last_release.as_ref(), &commits, None),
+        &logger,
+    )?;
+
+    let release_type = analyzed
+        .iter()
+        .filter_map(|output| serde_json::from_value(Value::String(output.to_lowercase())).ok())
+        .chain([analyze_commits(
+            commits.iter().map(|commit| commit.message.as_str()),
+            &config.release_rules,
+        )])
+        .max()
+        .unwrap_or(ReleaseType::None);
+
+    let release_type = if updated_dependencies.is_empty() {
+        release_type
+    } else {
+        logger.info(format!(
+            "Dependencies updated: {}",
+            updated_dependencies
+                .iter()
+                .map(|dependency| format!("{} {}", dependency.name, dependency.version))
+                .collect::<Vec<String>>()
+                .join(", ")
+        ));
+
+        release_type.max(ReleaseType::Patch)
+    };
+
+    context.logger.debug(format!(
+        "Analysis of the commits: {:?} release",
+        release_type
+    ));
+
+    let version = match &last_release {
+        Some(last_release) => {
+            let version =
+                Version::parse(&last_release.version).expect("Tag version should be valid");
+
+            next_version(&version, release_type)
+        }
+        None => (release_type != ReleaseType::None).then(|| {
+            Version::parse(&config.initial_version).expect("Initial version should be valid")
+        }),
+    };
+
+    let next_release = match version {
+        Some(version) => {
+            let version = version.to_string();
+            let git_tag = tag_format.replace("${version}", &version);
+
+            let mut next_release = NextRelease {
+                notes: String::new(),
+                version,
+                git_tag,
+                git_head: head.to_string(),
+                release_type,
+                channel: get_channel(context, repo),
+                updated_dependencies: updated_dependencies.to_vec(),
+            };
+
+            run_step(
+                &config.plugins,
+                Step::VerifyRelease,
+                &context.cwd,
+                &input(
+                    Step::VerifyRelease,
+                    last_release.as_ref(),
+                    &commits,
+                    Some(&next_release),
+                ),
+                &logger,
+            )?;
+
+            let notes = generate_notes(
+                &commits,
+                &NotesContext {
+                    repository_url: &config.repository_url,
+                    version: &next_release.version,
+                    git_tag: &next_release.git_tag,
+                    last_git_tag: last_release
+                        .as_ref()
+                        .map(|last_release| last_release.git_tag.as_str()),
+                    date: &today(),
+                    updated_dependencies,
+                },
+            );
+
+            let plugin_notes = run_step(
+                &config.plugins,
+                Step::GenerateNotes,
+                &context.cwd,
+                &input(
+                    Step::GenerateNotes,
+                    last_release.as_ref(),
+                    &commits,
+                    Some(&next_release),
+                ),
+                &logger,
+            )?;
+
+            next_release.notes = [notes.trim_end().to_string()]
+                .into_iter()
+                .chain(plugin_notes)
+                .collect::<Vec<String>>()
+                .join("\n\n");
+
+            Some(next_release)
+        }
+        None => None,
+    };
+
+    Ok(ReleasePlan {
+        package: package.cloned(),
+        last_release,
+        commits,
+        next_release,
+    })
+}
+
+/// Plan the releases of the `packages` of a monorepo, each after the packages it depends on.
+///
+/// A package is released, at least as a patch, when a package it depends on is, so the
+/// releases cascade to every package depending on a released one, directly or not.
+/// The plans are in the order the releases must be published in.
+pub fn plan_package_releases(
+    context: &Context,
+    repo: &GitRepo,
+    packages: &[Package],
+) -> Result<Vec<ReleasePlan>, SemanticReleaseError> {
+    let mut plans: Vec<ReleasePlan> = vec![];
+
+    for package in topological_order(packages)? {
+        let updated_dependencies: Vec<DependencyUpdate> = package
+            .dependencies
+            .iter()
+            .filter_map(|dependency| {
+                let plan = plans.iter().find(|plan| {
+                    plan.package
+                        .as_ref()
+                        .is_some_and(|package| package.name == *dependency)
+                })?;
+
+                plan.next_release
+                    .as_ref()
+                    .map(|next_release| DependencyUpdate {
+                        name: dependency.clone(),
+                        version: next_release.version.clone(),
+                    })
+            })
+            .collect();
+
+        // The commits of the packages within this one, e.g. of every other package for
+        // a package at the root of the repository, are theirs only.
+        let nested_paths: Vec<PathBuf> = packages
+            .iter()
+            .filter(|other| other.path != package.path && other.path.starts_with(&package.path))
+            .map(|other| other.path.clone())
+            .collect();
+
+        plans.push(plan_release(
+            context,
+            repo,
+            Some(package),
+            &nested_paths,
+            &updated_dependencies,
+        )?);
+    }
+
+    Ok(plans)
+}
+
+/// The JSON given to the plugins on stdin.
+pub(crate) fn plugin_input(
+    context: &Context,
+    step: Step,
+    package: Option<&Package>,
+    last_release: Option<&LastRelease>,
+    commits: &[Commit],
+    next_release: Option<&NextRelease>,
+) -> Value {
+    json!({
+        "step": step.name(),
+        "cwd": context.cwd,
+        "branch": context.branch,
+        "package": package,
+        "dry_run": context.config.dry_run,
+        "last_release": last_release,
+        "commits": commits,
+        "next_release": next_release,
+    })
+}
+
+/// Write a summary of the planned release: the last release, the commits considered,
+/// and the next version with its tag, channel and notes.
+pub fn write_summary(out: &mut impl Write, plan: &ReleasePlan, color: bool) -> io::Result<()> {
+    if let Some(package) = &plan.package {
+        writeln!(
+            out,
+            "Package: {} ({})",
+            package.name,
+            package.path.display()
+        )?;
+    }
+
+    match &plan.last_release {
+        Some(last_release) => writeln!(
+            out,
+            "Last release: {} ({})",
+            last_release.version, last_release.git_tag
+        )?,
+        None => writeln!(out, "Last release: none, this is the first release")?,
+    }
+
+    writeln!(
+        out,
+        "Commits since the last release: {}",
+        plan.commits.len()
+    )?;
+
+    for commit in &plan.commits {
+        let subject = commit.message.lines().next().unwrap_or_default();
+
+        writeln!(
+            out,
+            "  {} {}",
+            &commit.hash[..commit.hash.len().min(7)],
+            subject
+        )?;
+    }
+
+    let Some(next_release) = &plan.next_release else {
+        return writeln!(
+            out,
+            "Next release: none, there are no relevant changes since the last release"
+        );
+    };
+
+    writeln!(
+        out,
+        "Next release: {} ({:?})",
+        next_release.version, next_release.release_type
+    )?;
+    writeln!(out, "Tag: {}", next_release.git_tag)?;
+    writeln!(
+        out,
+        "Channel: {}",
+        next_release.channel.as_deref().unwrap_or("default")
+    )?;
+    if !next_release.updated_dependencies.is_empty() {
+        writeln!(
+            out,
+            "Updated dependencies: {}",
+            next_release
+                .updated_dependencies
+                .iter()
+                .map(|dependency| format!("{} {}", dependency.name, dependency.version))
+                .collect::<Vec<String>>()
+                .join(", ")
+        )?;
+    }
+
+    writeln!(out, "Release notes:")?;
+    writeln!(out)?;
+
+    for line in render_markdown(&next_release.notes, color).lines() {
+        writeln!(out, "  {}", line)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use std::{fs, path::Path};
+
+    use git2::{Repository, RepositoryInitOptions, Signature, Time};
+
+    use super::*;
+    use crate::{ci::CiEnv, get_config::Config, logger::Logger};
+
+    /// A plugin recording the steps it runs and their input, releasing a minor version with extra notes.
+    const PLUGIN: &str = "cat > \"$1.json\"\n\
+                          echo \"$1\" >> steps.txt\n\
+                          case \"$1\" in\n\
+                            analyze_commits) echo minor ;;\n\
+                            generate_notes) echo 'Notes from the plugin' ;;\n\
+                          esac\n";
+
+    /// Create a repository in `dir` with a `v1.0.0` release followed by a fix, and
+    /// the context of a run on its `main` branch with the recording plugin.
+    pub(crate) fn release_repo(dir: &Path) -> (GitRepo, Context) {
+        let repo =
+            Repository::init_opts(dir, RepositoryInitOptions::new().initial_head("main")).unwrap();
+        let signature =
+            Signature::new("Jane", "jane@example.com", &Time::new(1704105000, 120)).unwrap();
+
+        let mut parent = None;
+
+        for message in ["feat: initial", "fix: handle empty input"] {
+            fs::write(dir.join("README.md"), message).unwrap();
+
+            let mut index = repo.index().unwrap();
+            index.add_path(Path::new("README.md")).unwrap();
+            index.write().unwrap();
+
+            let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+            let parents: Vec<git2::Commit> = parent
+                .iter()
+                .map(|oid| repo.find_commit(*oid).unwrap())
+                .collect();
+            let oid = repo
+                .commit(
+                    Some("HEAD"),
+                    &signature,
+                    &signature,
+                    message,
+                    &tree,
+                    &parents.iter().collect::<Vec<&git2::Commit>>(),
+                )
+                .unwrap();
+
+            if parent.is_none() {
+                repo.tag_lightweight("v1.0.0", &repo.find_object(oid, None).unwrap(), false)
+                    .unwrap();
+            }
+
+            parent = Some(oid);
+        }
+
+        fs::write(dir.join("plugin.sh"), PLUGIN).unwrap();
+
+        let context = Context {
+            cwd: dir.to_path_buf(),
+            is_ci: false,
+            is_pr: false,
+            branch: "main".to_string(),
+            config: Config {
+                plugins: vec!["sh plugin.sh".to_string()],
+                ..Config::default()
+            },
+            ci_env: CiEnv::default(),
+            logger: Logger::new(io::sink(), false, false, str::to_string),
+        };
+
+        (GitRepo::open(dir).unwrap(), context)
+    }
+
+    #[test]
+    fn test_plan_release() {
+        let dir =
+            std::env::temp_dir().join(format!("semantic-release-plan-{}", std::process::id()));
+        let (repo, mut context) = release_repo(&dir);
+        context.config.dry_run = true;
+
+        let plan = plan_release(&context, &repo, None, &[], &[]).unwrap();
+
+        let steps = fs::read_to_string(dir.join("steps.txt")).unwrap();
+        let input: Value =
+            serde_json::from_str(&fs::read_to_string(dir.join("verify_release.json")).unwrap())
+                .unwrap();
+        let tagged = repo.repository().find_reference("refs/tags/v1.1.0").is_ok();
+
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(
+            steps,
+            "verify_conditions\nanalyze_commits\nverify_release\ngenerate_notes\n"
+        );
+        assert_eq!(input["dry_run"], true);
+        assert_eq!(input["next_release"]["version"], "1.1.0");
+        assert!(!tagged);
+
+        assert_eq!(plan.last_release.unwrap().git_tag, "v1.0.0");
+        assert_eq!(plan.commits.len(), 1);
+
+        let next_release = plan.next_release.unwrap();
+
+        assert_eq!(next_release.version, "1.1.0");
+        assert_eq!(next_release.release_type, ReleaseType::Minor);
+        assert_eq!(next_release.channel, None);
+        assert!(next_release.notes.ends_with("\n\nNotes from the plugin"));
+    }
+
+    #[test]
+    fn test_plan_release_plugin_failure() {
+        let dir = std::env::temp_dir().join(format!(
+            "semantic-release-plan-failure-{}",
+            std::process::id()
+        ));
+        let (repo, mut context) = release_repo(&dir);
+        context.config.plugins.push("false".to_string());
+
+        let error = plan_release(&context, &repo, None, &[], &[]).unwrap_err();
+
+        let steps = fs::read_to_string(dir.join("steps.txt")).unwrap();
+
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(error.code(), "EPLUGINFAILED");
+        assert_eq!(steps, "verify_conditions\n");
+    }
+
+    #[test]
+    fn test_tag_version() {
+        assert_eq!(
+            tag_version("release-${version}-final", "release-2.0.0-final"),
+            Some(Version::new(2, 0, 0))
+        );
+        assert_eq!(tag_version("v${version}", "vnext"), None);
+        assert_eq!(tag_version("v.${version}", "vx1.0.0"), None);
+    }
+
+    #[test]
+    fn test_write_summary() {
+        let plan = ReleasePlan {
+            package: None,
+            last_release: Some(LastRelease {
+                version: "1.0.0".to_string(),
+                git_tag: "v1.0.0".to_string(),
+                git_head: "0779705ecc46cbced5059bcbadee7b8d254d4300".to_string(),
+            }),
+            commits: vec![Commit {
+                hash: "3d92276063e6ebb33d63e2d20bf23d405f9d4925".to_string(),
+                message: "fix: handle empty input\n\nDetails".to_string(),
+                ..Commit::default()
+            }],
+            next_release: Some(NextRelease {
+                version: "1.0.1".to_string(),
+                git_tag: "v1.0.1".to_string(),
+                git_head: "3d92276063e6ebb33d63e2d20bf23d405f9d4925".to_string(),
+                release_type: ReleaseType::Patch,
+                channel: None,
+                notes: "## 1.0.1 (2024-01-01)".to_string(),
+                updated_dependencies: vec![],
+            }),
+        };
+
+        let mut out = Vec::new();
+        write_summary(&mut out, &plan, false).unwrap();
+
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "Last release: 1.0.0 (v1.0.0)\n\
+             Commits since the last release: 1\n  \
+             3d92276 fix: handle empty input\n\
+             Next release: 1.0.1 (Patch)\n\
+             Tag: v1.0.1\n\
+             Channel: default\n\
+             Release notes:\n\n  \
+             ## 1.0.1 (2024-01-01)\n"
+        );
+    }
+}
@@ -0,0 +1,228 @@
+use std::{fs, path::Path};
+
+use jsonschema::paths::LocationSegment;
+use regex::Regex;
+use serde_json::Value;
+
+use crate::{
+    error::{AggregateError, SemanticReleaseError},
+    get_config::{line_column, load_config, parse_config, Config},
+    verify_context::verify_config,
+};
+
+/// The camelCase aliases accepted by the `#[serde(alias)]` of the options, along with
+/// the definition holding them, or `None` for the top-level options.
+const ALIASES: [(Option<&str>, &str, &str); 6] = [
+    (None, "dryRun", "dry_run"),
+    (None, "initialVersion", "initial_version"),
+    (None, "releaseRules", "release_rules"),
+    (None, "repositoryUrl", "repository_url"),
+    (None, "tagFormat", "tag_format"),
+    (Some("MonorepoConfig"), "tagFormat", "tag_format"),
+];
+
+/// The JSON Schema of the config files.
+///
+/// The camelCase aliases of the options are described along with their snake_case name.
+///
+/// # Example
+///
+/// ```
+/// # use semantic_release::validate_config::config_schema;
+/// let schema = config_schema();
+///
+/// assert!(schema["properties"]["branches"].is_object());
+/// assert_eq!(schema["properties"]["tagFormat"], schema["properties"]["tag_format"]);
+/// ```
+pub fn config_schema() -> Value {
+    let mut schema =
+        serde_json::to_value(schemars::schema_for!(Config)).expect("Schema should serialize");
+
+    for (definition, alias, name) in ALIASES {
+        let properties = match definition {
+            Some(definition) => &mut schema["definitions"][definition]["properties"],
+            None => &mut schema["properties"],
+        };
+
+        let property = properties[name].clone();
+
+        properties[alias] = property;
+    }
+
+    schema
+}
+
+/// Validate the config file at `file_path` against the [`config_schema`] and the checks
+/// of [`verify_config`], reporting every problem found.
+///
+/// Schema violations are reported with their location in the file. The config is only
+/// checked once loaded, with the shared configs it extends, when it matches the schema.
+pub fn validate_config(cwd: &Path, file_path: &Path) -> Result<(), AggregateError> {
+    let contents = fs::read_to_string(file_path).map_err(|_| SemanticReleaseError::NoConfig {
+        path: file_path.to_path_buf(),
+    })?;
+
+    let value: Value =
+        parse_config(file_path, &contents)?.ok_or_else(|| SemanticReleaseError::NoConfig {
+            path: file_path.to_path_buf(),
+        })?;
+
+    let validator = jsonschema::validator_for(&config_schema()).expect("Schema should be valid");
+
+    let mut errors: Vec<SemanticReleaseError> = validator
+        .iter_errors(&value)
+        .map(|error| {
+            let segments: Vec<String> = error
+                .instance_path()
+                .iter()
+                .filter_map(|segment| match segment {
+                    LocationSegment::Property(property) => Some(property.to_string()),
+                    LocationSegment::Index(_) => None,
+                })
+                .collect();
+
+            let location = locate(file_path, &contents, &segments);
+
+            SemanticReleaseError::InvalidConfig {
+                path: file_path.to_path_buf(),
+                line: location.map(|(line, _)| line),
+                column: location.map(|(_, column)| column),
+                message: format!("`{}` {}", error.instance_path(), error),
+            }
+        })
+        .collect();
+
+    if errors.is_empty() {
+        match load_config(cwd, Some(file_path), None) {
+            Ok(loaded) => errors.extend(verify_config(&loaded.config)),
+            Err(error) => errors.push(error),
+        }
+    }
+
+    if !errors.is_empty() {
+        return Err(AggregateError::new(errors));
+    }
+
+    Ok(())
+}
+
+/// Find the 1-based line and column of the option at `keys`.
+///
+/// The keys are looked up in order in the source, which finds the option for
+/// every format without keeping the spans of the parsed values.
+fn locate(file_path: &Path, contents: &str, keys: &[String]) -> Option<(usize, usize)> {
+    let start = match file_path.file_name().and_then(|name| name.to_str()) {
+        Some("Cargo.toml") => contents.find("[package.metadata.release")?,
+        Some("package.json") => contents.find("\"release\"")?,
+        _ => 0,
+    };
+
+    let mut offset = start;
+    let mut location = None;
+
+    for key in keys {
+        let re = Regex::new(&format!(
+            r#"(?m)(?:^|[\s{{,.\[])["']?({})["']?\s*[:=\].]"#,
+            regex::escape(key)
+        ))
+        .expect("should be a valid regex");
+
+        let key_match = re.captures(&contents[offset..])?.get(1)?;
+
+        offset += key_match.start();
+        location = Some(line_column(contents, offset));
+    }
+
+    location.or(Some(line_column(contents, start)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_locate() {
+        let contents = "branches = [\"main\"]\n\n[secrets]\nnames = 1\n";
+
+        assert_eq!(
+            locate(
+                Path::new("release.toml"),
+                contents,
+                &["secrets".to_string(), "names".to_string()]
+            ),
+            Some((4, 1))
+        );
+
+        let contents = "{\n  \"branches\": [\"main\"],\n  \"tagFormat\": 1\n}";
+
+        assert_eq!(
+            locate(
+                Path::new(".releaserc.json"),
+                contents,
+                &["branches".to_string()]
+            ),
+            Some((2, 4))
+        );
+    }
+
+    #[test]
+    fn test_reports_every_schema_error() {
+        let dir =
+            std::env::temp_dir().join(format!("semantic-release-validate-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+
+        let file_path = dir.join(".releaserc.yaml");
+        fs::write(
+            &file_path,
+            "branches: main\nci: yes please\nrelease_rules:\n  - type: docs\n    release: sometimes\n",
+        )
+        .unwrap();
+
+        let errors = validate_config(&dir, &file_path).unwrap_err();
+
+        fs::remove_dir_all(&dir).unwrap();
+
+        let lines = errors
+            .errors()
+            .iter()
+            .map(|error| match error {
+                SemanticReleaseError::InvalidConfig { line, .. } => *line,
+                error => panic!("unexpected error {}", error),
+            })
+            .collect::<Vec<Option<usize>>>();
+
+        assert_eq!(lines, vec![Some(1), Some(2), Some(5)]);
+    }
+
+    #[test]
+    fn test_validates_aliases() {
+        let dir = std::env::temp_dir().join(format!(
+            "semantic-release-validate-aliases-{}",
+            std::process::id()
+        ));
+        fs::create_dir_all(&dir).unwrap();
+
+        let file_path = dir.join(".releaserc.json");
+        fs::write(
+            &file_path,
+            "{\n  \"dryRun\": true,\n  \"tagFormat\": 1,\n  \"monorepo\": { \"tagFormat\": [] }\n}",
+        )
+        .unwrap();
+
+        let errors = validate_config(&dir, &file_path).unwrap_err();
+
+        fs::remove_dir_all(&dir).unwrap();
+
+        let mut lines = errors
+            .errors()
+            .iter()
+            .map(|error| match error {
+                SemanticReleaseError::InvalidConfig { line, .. } => *line,
+                error => panic!("unexpected error {}", error),
+            })
+            .collect::<Vec<Option<usize>>>();
+        lines.sort();
+
+        assert_eq!(lines, vec![Some(3), Some(4)]);
+    }
+}
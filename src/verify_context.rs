@@ -1,27 +1,194 @@
-use std::error::Error;
+use std::collections::HashSet;
 
-use crate::{context::Context, git::is_git_repo};
+use git2::Reference;
+use semver::Version;
+
+use crate::{
+    branches::is_glob,
+    context::Context,
+    error::{AggregateError, SemanticReleaseError},
+    get_config::Config,
+    git::GitRepo,
+};
 
 /// Checks if provided context is valid.
 ///
+/// `repo` is the repository opened from `context.cwd`, if any.
+///
+/// Every problem found is reported in the returned [`AggregateError`].
+pub fn verify_context(context: &Context, repo: Option<&GitRepo>) -> Result<(), AggregateError> {
+    let mut errors: Vec<SemanticReleaseError> = vec![];
 
-pub fn verify_context(context: &Context) -> Result<(), Box<dyn Error>> {
-    let mut errors: Vec<String> = vec![];
-
-    if !is_git_repo() {
-        errors.push("ENOGITREPO".to_owned());
+    if repo.is_none() {
+        errors.push(SemanticReleaseError::NoGitRepo {
+            cwd: context.cwd.clone(),
+        });
     } else if context.config.repository_url.is_empty() {
-        errors.push("ENOREPOURL".to_owned());
+        errors.push(SemanticReleaseError::NoRepoUrl);
     }
 
-    // TODO: add stuff about compile git tag template
-    //
+    errors.extend(verify_config(&context.config));
 
-    // TODO: validate branches
-
-    if errors.is_empty() {
-        return Err(errors.concat().into());
+    if !errors.is_empty() {
+        return Err(AggregateError::new(errors));
     }
 
     Ok(())
 }
+
+/// Checks the options of `config` that don't depend on the environment,
+/// returning every problem found.
+pub fn verify_config(config: &Config) -> Vec<SemanticReleaseError> {
+    let mut errors: Vec<SemanticReleaseError> = vec![];
+
+    let tag_format = &config.tag_format;
+
+    // Verify that compiling the `tag_format` produce a valid Git tag
+    let tag = tag_format.replace("${version}", "0.0.0");
+
+    if !Reference::is_valid_name(&format!("refs/tags/{}", tag)) {
+        errors.push(SemanticReleaseError::InvalidTagFormat {
+            tag_format: tag_format.clone(),
+        });
+    }
+
+    // Verify the `tag_format` contains the variable `version` only once
+    if tag_format.matches("${version}").count() != 1 {
+        errors.push(SemanticReleaseError::TagNoVersion {
+            tag_format: tag_format.clone(),
+        });
+    }
+
+    if Version::parse(&config.initial_version).is_err() {
+        errors.push(SemanticReleaseError::InvalidInitialVersion {
+            initial_version: config.initial_version.clone(),
+        });
+    }
+
+    if config.monorepo.enabled {
+        let tag_format = &config.monorepo.tag_format;
+        let tag = tag_format
+            .replace("${name}", "package")
+            .replace("${version}", "0.0.0");
+
+        if !Reference::is_valid_name(&format!("refs/tags/{}", tag)) {
+            errors.push(SemanticReleaseError::InvalidTagFormat {
+                tag_format: tag_format.clone(),
+            });
+        }
+
+        if tag_format.matches("${version}").count() != 1 {
+            errors.push(SemanticReleaseError::TagNoVersion {
+                tag_format: tag_format.clone(),
+            });
+        }
+
+        if !tag_format.contains("${name}") {
+            errors.push(SemanticReleaseError::TagNoName {
+                tag_format: tag_format.clone(),
+            });
+        }
+    }
+
+    errors.extend(config.secrets.verify());
+
+    let branches = &config.branches;
+
+    if branches.is_empty() {
+        errors.push(SemanticReleaseError::ReleaseBranches);
+    }
+
+    for branch in branches {
+        if !is_glob(branch) && !Reference::is_valid_name(&format!("refs/heads/{}", branch)) {
+            errors.push(SemanticReleaseError::InvalidBranchName {
+                branch: branch.clone(),
+            });
+        }
+    }
+
+    let mut seen = HashSet::new();
+    let mut duplicates: Vec<String> = vec![];
+
+    for branch in branches {
+        if !seen.insert(branch) && !duplicates.contains(branch) {
+            duplicates.push(branch.clone());
+        }
+    }
+
+    if !duplicates.is_empty() {
+        errors.push(SemanticReleaseError::DuplicateBranches { duplicates });
+    }
+
+    errors
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ci::CiEnv, logger::Logger};
+
+    fn context(tag_format: &str, branches: &[&str]) -> Context {
+        Context {
+            cwd: env!("CARGO_MANIFEST_DIR").into(),
+            is_ci: false,
+            is_pr: false,
+            branch: "main".to_string(),
+            config: Config {
+                branches: branches.iter().map(|branch| branch.to_string()).collect(),
+                repository_url: "https://github.com/Javimtib92/semantic-rs.git".to_string(),
+                tag_format: tag_format.to_string(),
+                ..Config::default()
+            },
+            ci_env: CiEnv::default(),
+            logger: Logger::new(std::io::sink(), false, false, str::to_string),
+        }
+    }
+
+    #[test]
+    fn test_valid_context() {
+        let repo = GitRepo::open(env!("CARGO_MANIFEST_DIR")).unwrap();
+        let context = context("v${version}", &["+([0-9])?(.{+([0-9]),x}).x", "main"]);
+
+        assert!(verify_context(&context, Some(&repo)).is_ok());
+    }
+
+    #[test]
+    fn test_reports_every_error() {
+        let mut context = context("v ${major}", &["main", "main", "bad..name"]);
+        context.config.initial_version = "1.0".to_string();
+
+        let errors = verify_context(&context, None).unwrap_err();
+
+        let codes = errors
+            .errors()
+            .iter()
+            .map(|error| error.code())
+            .collect::<Vec<&str>>();
+
+        assert_eq!(
+            codes,
+            vec![
+                "ENOGITREPO",
+                "EINVALIDTAGFORMAT",
+                "ETAGNOVERSION",
+                "EINVALIDINITIALVERSION",
+                "EINVALIDBRANCHNAME",
+                "EDUPLICATEBRANCHES"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_monorepo_tag_format() {
+        let mut context = context("v${version}", &["main"]);
+        context.config.monorepo.enabled = true;
+        context.config.monorepo.tag_format = "v${version}".to_string();
+
+        let codes = verify_config(&context.config)
+            .iter()
+            .map(|error| error.code())
+            .collect::<Vec<&str>>();
+
+        assert_eq!(codes, vec!["ETAGNONAME"]);
+    }
+}